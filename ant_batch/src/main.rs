@@ -0,0 +1,383 @@
+//! Runs the ant simulation without a window.
+//!
+//! Loads the colony plugins once, steps the world for a fixed number of ticks
+//! (or until every nest has died) and prints a summary of the round.
+//! Passing `--plugin` several times lets the libraries compete, one per nest.
+//! A round recorded with `--record` can be played back with `--replay`, which
+//! does not load any plugin.
+
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::Duration;
+
+use ant_sim::deaths::DeathCause;
+use ant_sim::persist;
+use ant_sim::replay::Replay;
+use ant_sim::scenario::Scenario;
+use ant_sim::{ground::Ground, AntFn, ColonyBehavior, MemorySizeFn, NestFn, ResetFn};
+use common::abi::{self, AbiVersionFn, LayoutHashFn};
+use common::helper::config::Config;
+use libloading::{Library, Symbol};
+
+const USAGE: &str =
+    "Usage: ant_batch [--plugin <path>]... [--config <file> | --scenario <file>] [--ticks <n>] [--seed <n>] [--check-energy] \
+                     [--load <snapshot>] [--save <snapshot>] [--record <replay>]\n       \
+     ant_batch --replay <replay> [--check-energy]";
+
+const DEFAULT_TICKS: usize = 10000;
+
+struct Options {
+    plugins: Vec<PathBuf>,
+    /// [`DEFAULT_TICKS`] if not given
+    ticks: Option<usize>,
+    seed: Option<u64>,
+    check_energy: bool,
+    /// RON or TOML file replacing the default config
+    config: Option<PathBuf>,
+    /// Hand-made map, brings its own config
+    scenario: Option<PathBuf>,
+    /// Continue a saved world instead of starting a new round
+    load: Option<PathBuf>,
+    save: Option<PathBuf>,
+    record: Option<PathBuf>,
+    /// Play a recorded round back instead of running the plugins
+    replay: Option<PathBuf>,
+}
+
+impl Options {
+    fn from_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+        let mut options = Options {
+            plugins: Vec::new(),
+            ticks: None,
+            seed: None,
+            check_energy: false,
+            config: None,
+            scenario: None,
+            load: None,
+            save: None,
+            record: None,
+            replay: None,
+        };
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--plugin" => {
+                    let path = args.next().ok_or("--plugin needs a path")?;
+                    options.plugins.push(PathBuf::from(path));
+                }
+                "--ticks" => {
+                    let ticks = args.next().ok_or("--ticks needs a number")?;
+                    options.ticks = Some(
+                        ticks
+                            .parse()
+                            .map_err(|_| format!("'{}' is not a valid number of ticks", ticks))?,
+                    );
+                }
+                "--seed" => {
+                    let seed = args.next().ok_or("--seed needs a number")?;
+                    options.seed = Some(
+                        seed.parse()
+                            .map_err(|_| format!("'{}' is not a valid seed", seed))?,
+                    );
+                }
+                "--check-energy" => options.check_energy = true,
+                "--config" => {
+                    let path = args.next().ok_or("--config needs a path")?;
+                    options.config = Some(PathBuf::from(path));
+                }
+                "--scenario" => {
+                    let path = args.next().ok_or("--scenario needs a path")?;
+                    options.scenario = Some(PathBuf::from(path));
+                }
+                "--load" => {
+                    let path = args.next().ok_or("--load needs a path")?;
+                    options.load = Some(PathBuf::from(path));
+                }
+                "--save" => {
+                    let path = args.next().ok_or("--save needs a path")?;
+                    options.save = Some(PathBuf::from(path));
+                }
+                "--record" => {
+                    let path = args.next().ok_or("--record needs a path")?;
+                    options.record = Some(PathBuf::from(path));
+                }
+                "--replay" => {
+                    let path = args.next().ok_or("--replay needs a path")?;
+                    options.replay = Some(PathBuf::from(path));
+                }
+                "--help" | "-h" => {
+                    println!("{}", USAGE);
+                    process::exit(0);
+                }
+                other => return Err(format!("Unknown argument '{}'", other)),
+            }
+        }
+
+        if options.config.is_some() && options.scenario.is_some() {
+            return Err("--config and --scenario cannot be combined".to_string());
+        }
+
+        // A loaded world keeps its seed and is not a new round that could be recorded
+        if options.load.is_some() {
+            let conflicting = [
+                ("--seed", options.seed.is_some()),
+                ("--record", options.record.is_some()),
+                ("--scenario", options.scenario.is_some()),
+            ];
+            if let Some((name, _)) = conflicting.iter().find(|(_, given)| *given) {
+                return Err(format!("--load and {} cannot be combined", name));
+            }
+        }
+
+        // A replay brings its own config and decisions and runs until it ends
+        if options.replay.is_some() {
+            let conflicting = [
+                ("--plugin", !options.plugins.is_empty()),
+                ("--ticks", options.ticks.is_some()),
+                ("--seed", options.seed.is_some()),
+                ("--config", options.config.is_some()),
+                ("--scenario", options.scenario.is_some()),
+                ("--load", options.load.is_some()),
+                ("--save", options.save.is_some()),
+                ("--record", options.record.is_some()),
+            ];
+            if let Some((name, _)) = conflicting.iter().find(|(_, given)| *given) {
+                return Err(format!("--replay and {} cannot be combined", name));
+            }
+        }
+
+        if options.plugins.is_empty() {
+            options
+                .plugins
+                .push(PathBuf::from("target/debug").join(libloading::library_filename("dynlib")));
+        }
+
+        Ok(options)
+    }
+}
+
+/// Checks the plugin ABI and looks up the plugin functions. The returned behavior must not outlive `lib`.
+unsafe fn load_behavior(lib: &Library) -> Result<ColonyBehavior, Box<dyn Error>> {
+    let version = lib.get::<AbiVersionFn>(b"abi_version\0").ok();
+    let layout_hash = lib.get::<LayoutHashFn>(b"abi_layout_hash\0").ok();
+    abi::check(version.map(|f| *f), layout_hash.map(|f| *f))?;
+
+    let ant_func: Symbol<AntFn> = lib.get(b"ant_update\0")?;
+    let nest_func: Symbol<NestFn> = lib.get(b"nest_update\0")?;
+    let reset_func: Symbol<ResetFn> = lib.get(b"reset\0")?;
+    // Plugins without per-ant state do not need to export a memory size
+    let memory_size = lib
+        .get::<MemorySizeFn>(b"ant_memory_size\0")
+        .map(|size_func| size_func())
+        .unwrap_or(0);
+
+    Ok(ColonyBehavior {
+        ant_func: *ant_func,
+        nest_func: *nest_func,
+        reset_func: *reset_func,
+        memory_size,
+    })
+}
+
+fn print_summary(ground: &Ground, colony_death: Option<usize>) {
+    println!("Seed:            {}", ground.seed());
+    println!("Ticks simulated: {}", ground.tick());
+    println!("Ants alive:      {}", ground.num_ants());
+    println!("Food delivered:  {}", ground.food_delivered());
+    for nest in ground.nest_list() {
+        println!("Nest {} energy:  {}", nest.id, nest.energy);
+    }
+    for score in ground.score().colonies() {
+        println!(
+            "Colony {} score: food {}, spawned {}, ant-ticks {}, energy {}, survived {} ticks, fitness {:.1}",
+            score.colony,
+            score.food_delivered,
+            score.ants_spawned,
+            score.ant_ticks,
+            score.energy_integral,
+            score.survival_ticks,
+            score.fitness(&ground.config.score)
+        );
+        let deaths: Vec<String> = DeathCause::ALL
+            .iter()
+            .map(|&cause| format!("{} {}", cause, ground.deaths().count(score.colony, cause)))
+            .collect();
+        println!("Colony {} deaths: {}", score.colony, deaths.join(", "));
+    }
+    match colony_death {
+        Some(tick) => println!("All colonies died at tick {}", tick),
+        None => println!("Surviving colonies: {}", ground.num_colonies()),
+    }
+}
+
+/// The world of a snapshot was built for its own config, a new one must not change its layout
+fn check_fits_snapshot(config: &Config, saved: &Config) -> Result<(), String> {
+    if config.world.size() != saved.world.size() {
+        return Err(format!(
+            "the world is {} x {}, not {} x {}",
+            saved.world.width, saved.world.height, config.world.width, config.world.height
+        ));
+    }
+    if config.pheromones.cell_size != saved.pheromones.cell_size {
+        return Err(format!(
+            "the pheromone cells are {} wide, not {}",
+            saved.pheromones.cell_size, config.pheromones.cell_size
+        ));
+    }
+    Ok(())
+}
+
+fn play_replay(path: &Path, check_energy: bool) {
+    let replay = match Replay::load(path) {
+        Ok(replay) => replay,
+        Err(e) => {
+            eprintln!("Unable to load replay {}: {}", path.display(), e);
+            process::exit(1);
+        }
+    };
+
+    let mut ground = replay.ground();
+    ground.config.general.check_energy = check_energy;
+
+    let mut colony_death = None;
+    for record in &replay.ticks {
+        if let Err(e) = ground.replay_tick(Duration::from_millis(16), record) {
+            eprintln!("Unable to play replay {}: {}", path.display(), e);
+            process::exit(1);
+        }
+
+        if ground.num_colonies() == 0 {
+            colony_death = Some(ground.tick());
+            break;
+        }
+    }
+
+    print_summary(&ground, colony_death);
+}
+
+fn main() {
+    let options = match Options::from_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}", e);
+            eprintln!("{}", USAGE);
+            process::exit(1);
+        }
+    };
+
+    if let Some(path) = &options.replay {
+        play_replay(path, options.check_energy);
+        return;
+    }
+
+    // The libraries have to stay loaded as long as their behaviors are used
+    let mut libs = Vec::new();
+    let mut behaviors = Vec::new();
+    for path in &options.plugins {
+        let loaded = unsafe {
+            Library::new(path)
+                .map_err(Box::<dyn Error>::from)
+                .and_then(|lib| Ok((load_behavior(&lib)?, lib)))
+        };
+        match loaded {
+            Ok((behavior, lib)) => {
+                behaviors.push(behavior);
+                libs.push(lib);
+            }
+            Err(e) => {
+                eprintln!("Unable to load dynamic lib {}: {}", path.display(), e);
+                process::exit(1);
+            }
+        }
+    }
+
+    let mut config = options
+        .config
+        .as_ref()
+        .map(|path| match persist::load_config(path) {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!("Unable to load config {}: {}", path.display(), e);
+                process::exit(1);
+            }
+        });
+    if let Some(path) = &options.scenario {
+        match Scenario::load(path) {
+            Ok(scenario) => config = Some(scenario.to_config()),
+            Err(e) => {
+                eprintln!("Unable to load scenario {}: {}", path.display(), e);
+                process::exit(1);
+            }
+        }
+    }
+
+    let mut ground = match &options.load {
+        Some(path) => match Ground::load(path) {
+            // A given config replaces the one saved with the world
+            Ok(mut ground) => {
+                if let Some(config) = &config {
+                    if let Err(e) = check_fits_snapshot(config, &ground.config) {
+                        eprintln!("The config does not fit snapshot {}: {}", path.display(), e);
+                        process::exit(1);
+                    }
+                    ground.config = config.clone();
+                }
+                ground
+            }
+            Err(e) => {
+                eprintln!("Unable to load snapshot {}: {}", path.display(), e);
+                process::exit(1);
+            }
+        },
+        None => {
+            // Every plugin gets a colony of its own, fixed nest positions have to be enough for all
+            let mut config = config.clone().unwrap_or_default();
+            config.nests.colonies = config.nests.colonies.max(behaviors.len() as u32);
+            if let Err(e) = config.validate() {
+                eprintln!("The config does not fit {} plugins: {}", behaviors.len(), e);
+                process::exit(1);
+            }
+
+            let mut ground = Ground::new(config);
+            if let Some(seed) = options.seed {
+                ground.config.general.seed = seed;
+            }
+            ground.set_recording(options.record.is_some());
+            ground.start_new_round();
+            ground
+        }
+    };
+    ground.config.general.check_energy = options.check_energy;
+
+    let mut colony_death = None;
+    for _ in 0..options.ticks.unwrap_or(DEFAULT_TICKS) {
+        ground.update(Duration::from_millis(16), &behaviors);
+
+        if ground.num_colonies() == 0 {
+            colony_death = Some(ground.tick());
+            break;
+        }
+    }
+
+    print_summary(&ground, colony_death);
+
+    if let Some(path) = &options.record {
+        let saved = ground.replay().map(|replay| replay.save(path));
+        match saved {
+            Some(Ok(())) => (),
+            Some(Err(e)) => {
+                eprintln!("Unable to save replay {}: {}", path.display(), e);
+                process::exit(1);
+            }
+            None => eprintln!("Nothing recorded, --record only works for new rounds"),
+        }
+    }
+
+    if let Some(path) = &options.save {
+        if let Err(e) = ground.save(path) {
+            eprintln!("Unable to save snapshot {}: {}", path.display(), e);
+            process::exit(1);
+        }
+    }
+}
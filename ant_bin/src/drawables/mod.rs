@@ -1,91 +1,51 @@
 use common::{
+    animals::ant::Ant,
     buildings::Nest,
-    helper::{
-        config::{AntConfig, NestConfig},
-        Color, Rotation, Vector2D, BLUE,
-    },
+    helper::{Rotation, Vector2D, BLUE, GRAY, RED},
     items::food::FoodPellet,
+    terrain::Obstacle,
 };
 use glium::{Display, Frame, Texture2d};
 
 use crate::{
-    primitives::rectangle::Rectangle,
-    support::{camera::Camera, textures::TextureContainer},
+    primitives::{rectangle::Rectangle, shape::Shape},
+    support::camera::Camera,
 };
 
 pub struct AntDrawable {
-    pub ant: common::animals::ant::Ant,
     rect: Rectangle,
 }
 
 impl AntDrawable {
-    pub fn new(id: usize, config: &AntConfig, display: &Display) -> AntDrawable {
-        let size = Vector2D::new(16.0, 7.0);
-        let position = Vector2D::new(50.0, 50.0);
-        let rotation = Rotation::new_rad(0.0f32);
-        let color = Color::new(1.0f32, 0.0f32, 0.0f32, 1.0f32);
-
+    pub fn new(display: &Display) -> AntDrawable {
         AntDrawable {
-            ant: common::animals::ant::Ant {
-                id,
-                position,
-                color,
-                rotation,
-                size,
-                speed: config.speed,
-                angular_speed: config.angular_speed,
-                energy: config.max_energy,
-                max_energy: config.max_energy,
-                mouth_reach: config.mouth_reach,
-                rounds_to_energy_loss: config.energy_loss_rounds,
-                carrying: 0,
-            },
-            rect: Rectangle::new(size, position, rotation, color, display),
+            rect: Rectangle::new(
+                Vector2D::new(16.0, 7.0),
+                Vector2D::new(0.0, 0.0),
+                Rotation::new_rad(0.0),
+                RED,
+                display,
+            ),
         }
     }
 
-    pub fn new_at_pos(
-        id: usize,
-        config: &AntConfig,
-        texture_container: &TextureContainer,
-        pos: Vector2D,
-        display: &Display,
-    ) -> AntDrawable {
-        let mut ant_drawable = AntDrawable::new(id, config, display);
-        ant_drawable.ant.position = pos;
-        ant_drawable
-    }
-
-    pub fn draw(&mut self, texture: &Texture2d, target: &mut Frame, cam: &Camera) {
-        self.rect.position = self.ant.position; //- 0.5 * self.size; // * self.rotation);
-        self.rect.rotation = self.ant.rotation;
+    pub fn draw(&mut self, ant: &Ant, texture: &Texture2d, target: &mut Frame, cam: &Camera) {
+        self.rect.position = ant.position; //- 0.5 * self.size; // * self.rotation);
+        self.rect.rotation = ant.rotation;
         self.rect.draw(texture, target, cam);
     }
 }
 
 pub struct FoodPelletDrawable {
-    pub food: FoodPellet,
-    rect: crate::primitives::rectangle::Rectangle,
+    rect: Rectangle,
 }
 
 impl FoodPelletDrawable {
-    pub fn new_at_pos(
-        id: usize,
-        pos: Vector2D,
-        nut: u32,
-        display: &Display,
-        bite_size: u32,
-    ) -> FoodPelletDrawable {
+    pub fn new(display: &Display) -> FoodPelletDrawable {
         FoodPelletDrawable {
-            food: FoodPellet {
-                position: pos,
-                nutrition: nut,
-                bite_size,
-                id,
-            },
-            rect: crate::primitives::rectangle::Rectangle::new(
+            rect: Rectangle::new(
                 Vector2D::new(5.0, 5.0),
-                pos,
+                Vector2D::new(0.0, 0.0),
                 Rotation::new_rad(0.0),
                 BLUE,
                 display,
@@ -93,34 +53,28 @@ impl FoodPelletDrawable {
         }
     }
 
-    pub fn draw(&mut self, texture: &Texture2d, target: &mut Frame, cam: &Camera) {
+    pub fn draw(
+        &mut self,
+        food: &FoodPellet,
+        texture: &Texture2d,
+        target: &mut Frame,
+        cam: &Camera,
+    ) {
+        self.rect.position = food.position;
         self.rect.draw(texture, target, cam);
     }
 }
 
 pub struct NestDrawable {
-    pub nest: Nest,
-    rect: crate::primitives::rectangle::Rectangle,
+    rect: Rectangle,
 }
 
 impl NestDrawable {
-    pub fn new_at_pos(
-        id: usize,
-        pos: Vector2D,
-        config: &NestConfig,
-        texture_container: &TextureContainer,
-        display: &Display,
-    ) -> NestDrawable {
+    pub fn new(display: &Display) -> NestDrawable {
         NestDrawable {
-            nest: Nest {
-                id,
-                energy: config.start_energy,
-                pos,
-                rounds_to_energy_loss: config.energy_loss_rounds,
-            },
-            rect: crate::primitives::rectangle::Rectangle::new(
+            rect: Rectangle::new(
                 Vector2D::new(50.0, 50.0),
-                pos,
+                Vector2D::new(0.0, 0.0),
                 Rotation::new_rad(0.0),
                 common::helper::BLACK,
                 display,
@@ -128,7 +82,90 @@ impl NestDrawable {
         }
     }
 
-    pub fn draw(&mut self, texture: &Texture2d, target: &mut Frame, cam: &Camera) {
+    pub fn draw(&mut self, nest: &Nest, texture: &Texture2d, target: &mut Frame, cam: &Camera) {
+        self.rect.position = nest.pos;
         self.rect.draw(texture, target, cam);
     }
 }
+
+/// All obstacles of a ground in one shape, built again when the obstacles change
+pub struct ObstacleDrawable {
+    obstacles: Vec<Obstacle>,
+    shape: Shape,
+}
+
+impl ObstacleDrawable {
+    pub fn new(obstacles: &[Obstacle], display: &Display) -> ObstacleDrawable {
+        let triangles: Vec<[Vector2D; 3]> = obstacles
+            .iter()
+            .flat_map(|obstacle| triangulate(&obstacle.outline(32)))
+            .collect();
+
+        ObstacleDrawable {
+            obstacles: obstacles.to_vec(),
+            shape: Shape::new(&triangles, GRAY, display),
+        }
+    }
+
+    pub fn fits(&self, obstacles: &[Obstacle]) -> bool {
+        self.obstacles == obstacles
+    }
+
+    pub fn draw(&self, target: &mut Frame, cam: &Camera) {
+        self.shape.draw(target, cam);
+    }
+}
+
+fn cross(a: Vector2D, b: Vector2D) -> f32 {
+    a.x() * b.y() - a.y() * b.x()
+}
+
+/// Splits an outline that does not cross itself into triangles by cutting off one ear after the other
+fn triangulate(outline: &[Vector2D]) -> Vec<[Vector2D; 3]> {
+    let mut remaining = outline.to_vec();
+    let area: f32 = (0..remaining.len())
+        .map(|i| cross(remaining[i], remaining[(i + 1) % remaining.len()]))
+        .sum();
+    if area < 0.0 {
+        remaining.reverse();
+    }
+
+    let mut triangles = Vec::new();
+    while remaining.len() > 3 {
+        let len = remaining.len();
+        let ear = (0..len).find(|&i| {
+            let (a, b, c) = (
+                remaining[(i + len - 1) % len],
+                remaining[i],
+                remaining[(i + 1) % len],
+            );
+            let convex = cross(b - a, c - b) > 0.0;
+            convex
+                && !remaining.iter().any(|&p| {
+                    p != a
+                        && p != b
+                        && p != c
+                        && cross(b - a, p - a) >= 0.0
+                        && cross(c - b, p - b) >= 0.0
+                        && cross(a - c, p - c) >= 0.0
+                })
+        });
+
+        match ear {
+            Some(i) => {
+                triangles.push([
+                    remaining[(i + len - 1) % len],
+                    remaining[i],
+                    remaining[(i + 1) % len],
+                ]);
+                remaining.remove(i);
+            }
+            // Degenerate outline, draw what was found so far
+            None => return triangles,
+        }
+    }
+    if remaining.len() == 3 {
+        triangles.push([remaining[0], remaining[1], remaining[2]]);
+    }
+    triangles
+}
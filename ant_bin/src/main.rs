@@ -2,10 +2,7 @@ use std::cell::RefCell;
 use std::rc::Rc;
 use std::time::Duration;
 
-use common::animals::ant::{Ant, AntAction};
-use common::buildings::{Nest, NestAction};
-use common::helper::Vision;
-
+use ant_sim::{AntFn, ColonyBehavior, MemorySizeFn, NestFn, ResetFn};
 use dynamic_reload::{DynamicReload, Symbol};
 use plugins::Plugins;
 use support::simulator::Simulator;
@@ -16,9 +13,10 @@ mod plugins;
 mod primitives;
 mod support;
 
-pub type AntFunc<'a> = Symbol<'a, extern "C" fn(&Ant, &Vec<Vision>) -> AntAction>;
-pub type NestFunc<'a> = Symbol<'a, extern "C" fn(&Nest) -> NestAction>;
-pub type ResetFunc<'a> = Symbol<'a, extern "C" fn()>;
+pub type AntFunc<'a> = Symbol<'a, AntFn>;
+pub type NestFunc<'a> = Symbol<'a, NestFn>;
+pub type ResetFunc<'a> = Symbol<'a, ResetFn>;
+pub type MemorySizeFunc<'a> = Symbol<'a, MemorySizeFn>;
 
 fn main() {
     // let logic = Logic {};
@@ -37,16 +35,53 @@ fn main() {
         Duration::from_secs(2),
     );
 
-    unsafe {
-        // test_shared is generated in build.rs
-        match reload_handler.add_library("dynlib", dynamic_reload::PlatformName::Yes) {
-            Ok(lib) => plugs.add_plugin(&lib),
-            Err(e) => {
-                println!("Unable to load dynamic lib, err {:?}", e);
+    // Every library name given on the command line controls one colony, by default the
+    // sample ant_lib plays alone. `--replay <file>` shows a recorded round without any plugin,
+    // `--config <file>` replaces the default config and `--scenario <file>` starts on a hand-made map.
+    let mut lib_names: Vec<String> = Vec::new();
+    let mut replay_path = None;
+    let mut config_path = None;
+    let mut scenario_path = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let target = match arg.as_str() {
+            "--replay" => &mut replay_path,
+            "--config" => &mut config_path,
+            "--scenario" => &mut scenario_path,
+            _ => {
+                lib_names.push(arg);
+                continue;
+            }
+        };
+        match args.next() {
+            Some(path) => *target = Some(path),
+            None => {
+                println!("{} needs a path", arg);
                 return;
             }
         }
     }
+    // A replay brings its own config and decisions
+    if replay_path.is_some() {
+        if !lib_names.is_empty() || config_path.is_some() || scenario_path.is_some() {
+            println!("--replay cannot be combined with libraries, --config or --scenario");
+            return;
+        }
+    } else if lib_names.is_empty() {
+        lib_names.push("dynlib".to_string());
+    }
+
+    for name in &lib_names {
+        unsafe {
+            match reload_handler.add_library(name, dynamic_reload::PlatformName::Yes) {
+                Ok(lib) => plugs.add_plugin(name, &lib),
+                Err(e) => {
+                    println!("Unable to load dynamic lib {}, err {:?}", name, e);
+                    return;
+                }
+            }
+        }
+    }
 
     //
     // While this is running (printing a number) change return value in file src/test_shared.rs
@@ -56,6 +91,41 @@ fn main() {
     let system = crate::support::init(file!());
 
     let app = Rc::new(RefCell::new(Simulator::new(&system.display)));
+    if let Some(path) = config_path {
+        let mut app = app.borrow_mut();
+        app.config_path = path;
+        app.load_config();
+        if let Some(e) = app.config_error.take() {
+            println!("{}", e);
+            return;
+        }
+    }
+    if let Some(path) = scenario_path {
+        let mut app = app.borrow_mut();
+        app.scenario_path = path;
+        if let Err(e) = app.load_scenario() {
+            println!("{}", e);
+            return;
+        }
+    }
+    {
+        // Every library gets a colony of its own, fixed nest positions have to be enough for all
+        let config = &mut app.borrow_mut().ground.config;
+        config.nests.colonies = config.nests.colonies.max(lib_names.len() as u32);
+        if let Err(e) = config.validate() {
+            println!("The config does not fit {} libraries: {}", lib_names.len(), e);
+            return;
+        }
+    }
+    if let Some(path) = replay_path {
+        let mut app = app.borrow_mut();
+        app.replay_path = path;
+        app.play_replay();
+        if app.playback.is_none() {
+            println!("{}", app.replay_message.as_deref().unwrap_or_default());
+            return;
+        }
+    }
 
     let app_ui = Rc::clone(&app);
     let app_update = Rc::clone(&app);
@@ -74,17 +144,40 @@ fn main() {
                 reload_handler.update(&Plugins::reload_callback, &mut plugs);
             }
 
-            if !plugs.plugins.is_empty() {
-                let update_fun: AntFunc =
-                    unsafe { plugs.plugins[0].lib.get(b"ant_update\0").unwrap() };
+            app_update.borrow_mut().sync_renderer(display);
+
+            app_update.borrow_mut().plugin_errors = plugs.errors();
+
+            if app_update.borrow().playback.is_some() {
+                app_update.borrow_mut().update_playback(dt);
+            } else if plugs.is_ready() {
+                let behaviors: Vec<ColonyBehavior> = plugs
+                    .plugins
+                    .iter()
+                    .filter_map(|slot| slot.lib.as_ref())
+                    .map(|plugin| {
+                        let update_fun: AntFunc =
+                            unsafe { plugin.lib.get(b"ant_update\0").unwrap() };
+
+                        let nest_fun: NestFunc =
+                            unsafe { plugin.lib.get(b"nest_update\0").unwrap() };
+
+                        let reset_fun: ResetFunc = unsafe { plugin.lib.get(b"reset\0").unwrap() };
+
+                        // Plugins without per-ant state do not need to export a memory size
+                        let memory_size_fun: Option<MemorySizeFunc> =
+                            unsafe { plugin.lib.get(b"ant_memory_size\0").ok() };
 
-                let nest_fun: NestFunc =
-                    unsafe { plugs.plugins[0].lib.get(b"nest_update\0").unwrap() };
+                        ColonyBehavior {
+                            ant_func: *update_fun,
+                            nest_func: *nest_fun,
+                            reset_func: *reset_fun,
+                            memory_size: memory_size_fun.map_or(0, |size_fun| size_fun()),
+                        }
+                    })
+                    .collect();
 
-                let reset_fun: ResetFunc = unsafe { plugs.plugins[0].lib.get(b"reset\0").unwrap() };
-                app_update
-                    .borrow_mut()
-                    .update(dt, display, update_fun, nest_fun, reset_fun);
+                app_update.borrow_mut().update(dt, &behaviors);
             }
         },
         move |target, _display| {
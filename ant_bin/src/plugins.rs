@@ -1,26 +1,94 @@
+use std::path::PathBuf;
 use std::sync::Arc;
 
-use dynamic_reload::{Lib, UpdateState};
+use common::abi::{self, AbiVersionFn, LayoutHashFn};
+use dynamic_reload::{Lib, Symbol, UpdateState};
 
+use crate::{AntFunc, NestFunc, ResetFunc};
+
+/// One colony library. `lib` is `None` while it is being reloaded or when it was refused.
+pub struct PluginSlot {
+    pub name: String,
+    pub lib: Option<Arc<Lib>>,
+    pub error: Option<String>,
+    path: Option<PathBuf>,
+}
+
+/// The loaded colony libraries. The slot index is the colony the library controls.
 pub struct Plugins {
-    pub plugins: Vec<Arc<Lib>>,
+    pub plugins: Vec<PluginSlot>,
 }
 
 impl Plugins {
-    pub fn add_plugin(&mut self, plugin: &Arc<Lib>) {
-        self.plugins.push(plugin.clone());
+    /// Checks that the library was built against the same `common` and exports all plugin functions
+    fn verify(lib: &Lib) -> Result<(), String> {
+        unsafe {
+            let version: Option<Symbol<AbiVersionFn>> = lib.lib.get(b"abi_version\0").ok();
+            let layout_hash: Option<Symbol<LayoutHashFn>> = lib.lib.get(b"abi_layout_hash\0").ok();
+            abi::check(version.map(|f| *f), layout_hash.map(|f| *f)).map_err(|e| e.to_string())?;
+
+            let update_fun: Option<AntFunc> = lib.lib.get(b"ant_update\0").ok();
+            let nest_fun: Option<NestFunc> = lib.lib.get(b"nest_update\0").ok();
+            let reset_fun: Option<ResetFunc> = lib.lib.get(b"reset\0").ok();
+            if update_fun.is_none() || nest_fun.is_none() || reset_fun.is_none() {
+                return Err("plugin has to export ant_update, nest_update and reset".to_string());
+            }
+        }
+
+        Ok(())
+    }
+
+    fn accept(slot: &mut PluginSlot, lib: &Arc<Lib>) {
+        match Self::verify(lib) {
+            Ok(()) => {
+                slot.lib = Some(lib.clone());
+                slot.error = None;
+            }
+            Err(e) => {
+                println!("Refusing plugin {}: {}", slot.name, e);
+                slot.lib = None;
+                slot.error = Some(e);
+            }
+        }
+    }
+
+    pub fn add_plugin(&mut self, name: &str, plugin: &Arc<Lib>) {
+        let mut slot = PluginSlot {
+            name: name.to_string(),
+            lib: None,
+            error: None,
+            path: plugin.original_path.clone(),
+        };
+        Self::accept(&mut slot, plugin);
+        self.plugins.push(slot);
+    }
+
+    /// The simulation only runs while every colony has a valid library
+    pub fn is_ready(&self) -> bool {
+        !self.plugins.is_empty() && self.plugins.iter().all(|p| p.lib.is_some())
+    }
+
+    pub fn errors(&self) -> Vec<String> {
+        self.plugins
+            .iter()
+            .filter_map(|p| p.error.as_ref().map(|e| format!("{}: {}", p.name, e)))
+            .collect()
     }
 
     pub fn unload_plugins(&mut self, lib: &Arc<Lib>) {
-        for i in (0..self.plugins.len()).rev() {
-            if &self.plugins[i] == lib {
-                self.plugins.swap_remove(i);
+        for slot in &mut self.plugins {
+            if slot.path == lib.original_path {
+                slot.lib = None;
             }
         }
     }
 
     pub fn reload_plugin(&mut self, lib: &Arc<Lib>) {
-        Self::add_plugin(self, lib);
+        for slot in &mut self.plugins {
+            if slot.path == lib.original_path {
+                Self::accept(slot, lib);
+            }
+        }
     }
 
     // called when a lib needs to be reloaded.
@@ -1,2 +1,3 @@
 pub mod rectangle;
+pub mod shape;
 pub mod vertex;
@@ -0,0 +1,83 @@
+use super::vertex::Vertex;
+use crate::support::camera::Camera;
+use glium::{
+    index::{NoIndices, PrimitiveType},
+    uniform, Blend, Display, DrawParameters, Frame, Program, Surface, VertexBuffer,
+};
+
+use common::helper::{Color, Vector2D};
+
+/// Any number of triangles in one color, already in ground coordinates
+pub struct Shape {
+    vertex_buffer: VertexBuffer<Vertex>,
+    program: Program,
+}
+
+impl Shape {
+    pub fn new(triangles: &[[Vector2D; 3]], color: Color, display: &Display) -> Shape {
+        let shape: Vec<Vertex> = triangles
+            .iter()
+            .flatten()
+            .map(|corner| Vertex::new(corner.get_data(), color.get_data(), [0.0, 0.0]))
+            .collect();
+
+        let vertex_buffer = glium::VertexBuffer::new(display, &shape).unwrap();
+
+        let vertex_shader_src = r#"
+#version 330
+
+in vec2 position;
+in vec4 color;
+in vec2 uv;
+uniform mat4 view_mat;
+uniform mat4 proj_mat;
+out vec4 my_attr;
+
+void main() {
+    my_attr = color;
+    gl_Position = proj_mat * view_mat * vec4(position, 0.0, 1.0);
+}
+"#;
+
+        let fragment_shader_src = r#"
+#version 140
+
+in vec4 my_attr;
+out vec4 color;
+void main() {
+    color = my_attr;
+}
+"#;
+
+        let program =
+            glium::Program::from_source(display, vertex_shader_src, fragment_shader_src, None)
+                .unwrap();
+
+        Shape {
+            vertex_buffer,
+            program,
+        }
+    }
+
+    pub fn draw(&self, frame: &mut Frame, cam: &Camera) {
+        let uniforms = uniform! {
+            view_mat: cam.view_mat,
+            proj_mat: cam.proj_mat,
+        };
+
+        let draw_params = DrawParameters {
+            blend: Blend::alpha_blending(),
+            ..Default::default()
+        };
+
+        frame
+            .draw(
+                &self.vertex_buffer,
+                NoIndices(PrimitiveType::TrianglesList),
+                &self.program,
+                &uniforms,
+                &draw_params,
+            )
+            .unwrap();
+    }
+}
@@ -1,16 +1,52 @@
 use crate::{
-    ground,
+    ground::GroundRenderer,
     support::{self, camera::Camera},
-    AntFunc, NestFunc, ResetFunc,
 };
-use common::helper::Vector2D;
+use ant_sim::{ground::Ground, persist, replay::Replay, scenario::Scenario, ColonyBehavior};
+use common::helper::config::Config;
 use glium::{Display, Frame, Surface};
+use std::path::Path;
 use std::time::Duration;
 
+/// A replay being shown instead of the plugins
+pub struct Playback {
+    replay: Replay,
+    next_tick: usize,
+}
+
+impl Playback {
+    pub fn is_finished(&self) -> bool {
+        self.next_tick >= self.replay.ticks.len()
+    }
+}
+
 pub struct Simulator {
-    pub ground: ground::Ground,
+    pub ground: Ground,
+    pub renderer: GroundRenderer,
     pub cam: Camera,
     pub new_round_pending: bool,
+    /// Plugins that were refused, the simulation is paused while there are any
+    pub plugin_errors: Vec<String>,
+    /// File used by the Save and Load buttons
+    pub snapshot_path: String,
+    /// Result of the last save or load, shown in the UI
+    pub snapshot_message: Option<String>,
+    /// File used by the config buttons, TOML if it ends in `.toml` and RON otherwise
+    pub config_path: String,
+    /// Result of the last config save or load, shown in the UI
+    pub config_message: Option<String>,
+    /// Set when the last config load failed
+    pub config_error: Option<String>,
+    /// File used by the scenario button
+    pub scenario_path: String,
+    /// Result of the last scenario load, shown in the UI
+    pub scenario_message: Option<String>,
+    /// File used by the replay buttons
+    pub replay_path: String,
+    /// Result of the last replay action, shown in the UI
+    pub replay_message: Option<String>,
+    /// While set, the ground follows the replay and the plugins are not asked
+    pub playback: Option<Playback>,
 
     // technical
     pub size: [f32; 2],
@@ -18,38 +54,183 @@ pub struct Simulator {
 
 impl Simulator {
     pub fn new(display: &Display) -> Simulator {
-        let mut ground = ground::Ground::new_empty(Vector2D::new(1000.0, 1000.0), display);
+        let mut ground = Ground::new(Config::new());
 
-        ground.start_new_round(display);
+        ground.start_new_round();
+
+        let renderer = GroundRenderer::new(&ground, display);
 
         Simulator {
             ground,
+            renderer,
             cam: {
                 let mut cam = support::camera::Camera::new();
                 cam.position = [490.0, 470.0, -962.0];
                 cam
             },
             new_round_pending: false,
+            plugin_errors: Vec::new(),
+            snapshot_path: "snapshot.ron".to_string(),
+            snapshot_message: None,
+            config_path: "config.ron".to_string(),
+            config_message: None,
+            config_error: None,
+            scenario_path: "scenarios/two_bushes.ron".to_string(),
+            scenario_message: None,
+            replay_path: "replay.bin".to_string(),
+            replay_message: None,
+            playback: None,
             size: [1.0, 1.0],
         }
     }
 
-    pub fn update(
-        &mut self,
-        dt: Duration,
-        display: &Display,
-        ant_func: AntFunc,
-        nest_func: NestFunc,
-        reset_func: ResetFunc,
-    ) {
+    pub fn update(&mut self, dt: Duration, behaviors: &[ColonyBehavior]) {
         if self.new_round_pending {
-            self.ground.start_new_round(display);
+            self.ground.start_new_round();
             self.new_round_pending = false;
         }
 
         self.cam.update_view();
-        self.ground
-            .update(dt, display, ant_func, nest_func, reset_func);
+        self.ground.update(dt, behaviors);
+    }
+
+    /// Plays the next tick of the replay, the ground stays as it is once the replay is over
+    pub fn update_playback(&mut self, dt: Duration) {
+        self.cam.update_view();
+
+        let playback = match &mut self.playback {
+            Some(playback) if !playback.is_finished() => playback,
+            _ => return,
+        };
+
+        let record = &playback.replay.ticks[playback.next_tick];
+        match self.ground.replay_tick(dt, record) {
+            Ok(()) => playback.next_tick += 1,
+            Err(e) => {
+                self.replay_message = Some(format!("Replay stopped: {}", e));
+                playback.next_tick = playback.replay.ticks.len();
+            }
+        }
+    }
+
+    pub fn save_config(&mut self) {
+        let path = Path::new(&self.config_path);
+        self.config_message = Some(match persist::save_config(path, &self.ground.config) {
+            Ok(()) => format!("Saved {}", path.display()),
+            Err(e) => format!("Unable to save {}: {}", path.display(), e),
+        });
+    }
+
+    /// Replaces the config of the ground, the current round keeps running with it
+    pub fn load_config(&mut self) {
+        let path = Path::new(&self.config_path);
+        match persist::load_config(path) {
+            Ok(config) => {
+                self.ground.config = config;
+                self.config_error = None;
+                self.config_message = Some(format!("Loaded {}", path.display()));
+            }
+            Err(e) => {
+                let message = format!("Unable to load {}: {}", path.display(), e);
+                self.config_error = Some(message.clone());
+                self.config_message = Some(message);
+            }
+        }
+    }
+
+    /// Replaces the config with the one of the scenario and starts a new round on its map
+    pub fn load_scenario(&mut self) -> Result<(), String> {
+        let path = Path::new(&self.scenario_path);
+        let result = match Scenario::load(path) {
+            Ok(scenario) => {
+                self.ground.config = scenario.to_config();
+                self.playback = None;
+                self.new_round_pending = true;
+                self.scenario_message = Some(format!("Playing scenario '{}'", scenario.name));
+                Ok(())
+            }
+            Err(e) => Err(format!("Unable to load {}: {}", path.display(), e)),
+        };
+        if let Err(e) = &result {
+            self.scenario_message = Some(e.clone());
+        }
+        result
+    }
+
+    pub fn set_recording(&mut self, enabled: bool) {
+        self.ground.set_recording(enabled);
+        if enabled {
+            self.replay_message = Some("Recording starts with the next round".to_string());
+        }
+    }
+
+    pub fn save_replay(&mut self) {
+        let path = Path::new(&self.replay_path);
+        self.replay_message = Some(match self.ground.replay() {
+            Some(replay) => match replay.save(path) {
+                Ok(()) => format!("Saved {} ticks to {}", replay.ticks.len(), path.display()),
+                Err(e) => format!("Unable to save {}: {}", path.display(), e),
+            },
+            None => "Nothing recorded yet".to_string(),
+        });
+    }
+
+    pub fn play_replay(&mut self) {
+        let path = Path::new(&self.replay_path);
+        self.replay_message = Some(match Replay::load(path) {
+            Ok(replay) => {
+                self.ground = replay.ground();
+                self.new_round_pending = false;
+                let message = format!("Playing {} ticks of {}", replay.ticks.len(), path.display());
+                self.playback = Some(Playback {
+                    replay,
+                    next_tick: 0,
+                });
+                message
+            }
+            Err(e) => format!("Unable to load {}: {}", path.display(), e),
+        });
+    }
+
+    /// Leaves the replay and lets the plugins play a new round
+    pub fn stop_playback(&mut self) {
+        if self.playback.take().is_some() {
+            self.new_round_pending = true;
+            self.replay_message = None;
+        }
+    }
+
+    pub fn save_snapshot(&mut self) {
+        let path = Path::new(&self.snapshot_path);
+        self.snapshot_message = Some(match self.ground.save(path) {
+            Ok(()) => format!("Saved {}", path.display()),
+            Err(e) => format!("Unable to save {}: {}", path.display(), e),
+        });
+    }
+
+    pub fn load_snapshot(&mut self) {
+        let path = Path::new(&self.snapshot_path);
+        self.snapshot_message = Some(match Ground::load(path) {
+            Ok(ground) => {
+                self.ground = ground;
+                self.new_round_pending = false;
+                format!("Loaded {}", path.display())
+            }
+            Err(e) => format!("Unable to load {}: {}", path.display(), e),
+        });
+    }
+
+    /// Rebuilds the renderer if a loaded ground has another size
+    pub fn sync_renderer(&mut self, display: &Display) {
+        if !self.renderer.fits(&self.ground) {
+            self.renderer = GroundRenderer::new(&self.ground, display);
+
+            // Keep the whole ground in view, the default camera shows a 1000x1000 ground
+            let size = self.ground.size();
+            let scale = size.x().max(size.y()) / 1000.0;
+            self.cam.position = [490.0 * scale, 470.0 * scale, -962.0 * scale];
+        }
+        self.renderer.sync_obstacles(&self.ground, display);
     }
 
     pub fn draw(&mut self, frame: &mut Frame) {
@@ -58,6 +239,6 @@ impl Simulator {
             frame.get_dimensions().1 as f32,
         ];
         self.cam.update_proj(frame);
-        self.ground.draw(frame, &self.cam);
+        self.renderer.draw(&self.ground, frame, &self.cam);
     }
 }
@@ -4,7 +4,11 @@ use imgui::{ChildWindow, Condition, Window};
 use std::{cell::RefCell, rc::Rc};
 
 use super::simulator::Simulator;
+use ant_sim::deaths::DeathCause;
+use common::animals::ant::Caste;
 use common::helper;
+use common::helper::config::Placement;
+use common::helper::Vector2D;
 
 pub fn camera_control(ui: &mut Ui, app_ui: &Rc<RefCell<Simulator>>) {
     Window::new("Camera Control")
@@ -37,15 +41,157 @@ pub fn camera_control(ui: &mut Ui, app_ui: &Rc<RefCell<Simulator>>) {
         });
 }
 
+/// Choice of a placement strategy and its parameters, used for the next round
+fn placement_control(ui: &Ui, label: &str, placement: &mut Placement, size: Vector2D) {
+    let names = ["Uniform", "Clustered", "Fixed", "Ring", "Mirrored"];
+    let mut current = match placement {
+        Placement::Uniform => 0,
+        Placement::Clustered { .. } => 1,
+        Placement::Fixed { .. } => 2,
+        Placement::Ring { .. } => 3,
+        Placement::Mirrored => 4,
+    };
+
+    if ui.combo_simple_string(format!("{} Placement", label), &mut current, &names) {
+        *placement = match current {
+            1 => Placement::Clustered {
+                patches: 3,
+                radius: 50.0,
+            },
+            // Fixed points can only be set in a config file, start with the center
+            2 => Placement::Fixed {
+                points: vec![size * 0.5],
+            },
+            3 => Placement::Ring { radius: 200.0 },
+            4 => Placement::Mirrored,
+            _ => Placement::Uniform,
+        };
+    }
+
+    match placement {
+        Placement::Clustered { patches, radius } => {
+            Drag::new(format!("{} Patches", label))
+                .range(1, 100)
+                .build(ui, patches);
+            Drag::new(format!("{} Patch Radius", label))
+                .range(0.0, 1000.0)
+                .build(ui, radius);
+        }
+        Placement::Fixed { points } => ui.text(format!("{} fixed points", points.len())),
+        Placement::Ring { radius } => {
+            Drag::new(format!("{} Ring Radius", label))
+                .range(0.0, 1000.0)
+                .build(ui, radius);
+        }
+        Placement::Uniform | Placement::Mirrored => (),
+    }
+}
+
 pub fn simulation_control(ui: &mut Ui, app_ui: &Rc<RefCell<Simulator>>) {
     Window::new("Simulation Control")
         .size([300.0, 300.0], Condition::FirstUseEver)
         .position([50.0, 400.0], Condition::FirstUseEver)
         .build(ui, || {
+            // Typed in values are not held to the ranges, so edits that fail validation are undone
+            let last_valid = app_ui.borrow().ground.config.clone();
+
+            for error in &app_ui.borrow().plugin_errors {
+                ui.text_colored(helper::RED.get_data(), error);
+            }
+            if !app_ui.borrow().plugin_errors.is_empty() {
+                ui.text("Simulation paused until the plugins are rebuilt.");
+                ui.separator();
+            }
+
             if ui.button_with_size("Start new sim", [150.0, 50.0]) {
+                app_ui.borrow_mut().playback = None;
                 app_ui.borrow_mut().new_round_pending = true;
             }
 
+            ui.input_text("File", &mut app_ui.borrow_mut().snapshot_path)
+                .build();
+            if ui.button("Save") {
+                app_ui.borrow_mut().save_snapshot();
+            }
+            ui.same_line();
+            if ui.button("Load") {
+                app_ui.borrow_mut().load_snapshot();
+            }
+            if let Some(message) = &app_ui.borrow().snapshot_message {
+                ui.text(message);
+            }
+
+            ui.input_text("Config", &mut app_ui.borrow_mut().config_path)
+                .build();
+            if ui.button("Save config") {
+                app_ui.borrow_mut().save_config();
+            }
+            ui.same_line();
+            if ui.button("Load config") {
+                app_ui.borrow_mut().load_config();
+            }
+            if let Some(message) = &app_ui.borrow().config_message {
+                ui.text(message);
+            }
+
+            ui.input_text("Scenario", &mut app_ui.borrow_mut().scenario_path)
+                .build();
+            if ui.button("Load scenario") {
+                // Failures are shown with the message below
+                let _ = app_ui.borrow_mut().load_scenario();
+            }
+            if let Some(message) = &app_ui.borrow().scenario_message {
+                ui.text(message);
+            }
+
+            ui.input_text("Replay", &mut app_ui.borrow_mut().replay_path)
+                .build();
+            let mut recording = app_ui.borrow().ground.is_recording();
+            if ui.checkbox("Record", &mut recording) {
+                app_ui.borrow_mut().set_recording(recording);
+            }
+            ui.same_line();
+            if ui.button("Save Replay") {
+                app_ui.borrow_mut().save_replay();
+            }
+            ui.same_line();
+            if ui.button("Play Replay") {
+                app_ui.borrow_mut().play_replay();
+            }
+            if app_ui.borrow().playback.is_some() {
+                ui.same_line();
+                if ui.button("Stop") {
+                    app_ui.borrow_mut().stop_playback();
+                }
+            }
+            if let Some(message) = &app_ui.borrow().replay_message {
+                ui.text(message);
+            }
+
+            ui.separator();
+            ui.text_colored(helper::RED.get_data(), "General");
+
+            Drag::new("Seed").build(ui, &mut app_ui.borrow_mut().ground.config.general.seed);
+
+            Drag::new("World Width")
+                .range(100.0, 10000.0)
+                .build(ui, &mut app_ui.borrow_mut().ground.config.world.width);
+
+            Drag::new("World Height")
+                .range(100.0, 10000.0)
+                .build(ui, &mut app_ui.borrow_mut().ground.config.world.height);
+
+            // Obstacles can only be set in a config file
+            ui.text(format!(
+                "{} obstacles",
+                app_ui.borrow().ground.config.world.obstacles.len()
+            ));
+
+            ui.checkbox(
+                "Check Energy",
+                &mut app_ui.borrow_mut().ground.config.general.check_energy,
+            );
+
             ui.separator();
             ui.text_colored(helper::RED.get_data(), "Food");
 
@@ -64,39 +210,189 @@ pub fn simulation_control(ui: &mut Ui, app_ui: &Rc<RefCell<Simulator>>) {
             Drag::new("Food Start Amount")
                 .build(ui, &mut app_ui.borrow_mut().ground.config.food.start_amount);
 
+            {
+                let mut app = app_ui.borrow_mut();
+                let size = app.ground.config.world.size();
+                placement_control(ui, "Food", &mut app.ground.config.food.placement, size);
+            }
+
+            Drag::new("Food Sources")
+                .range(0, 100)
+                .build(ui, &mut app_ui.borrow_mut().ground.config.food.sources);
+
+            Drag::new("Source Capacity").range(1, u32::MAX).build(
+                ui,
+                &mut app_ui.borrow_mut().ground.config.food.source_capacity,
+            );
+
+            Drag::new("Source Regrowth").build(
+                ui,
+                &mut app_ui.borrow_mut().ground.config.food.source_regrowth,
+            );
+
+            {
+                let mut app = app_ui.borrow_mut();
+                let size = app.ground.config.world.size();
+                placement_control(
+                    ui,
+                    "Source",
+                    &mut app.ground.config.food.source_placement,
+                    size,
+                );
+            }
+
             ui.separator();
             ui.text_colored(helper::RED.get_data(), "Ants");
 
             Drag::new("Ant Max Energy")
+                .range(1, u32::MAX)
                 .build(ui, &mut app_ui.borrow_mut().ground.config.ants.max_energy);
 
-            Drag::new("Ant Speed").build(ui, &mut app_ui.borrow_mut().ground.config.ants.speed);
-
             Drag::new("Ant Angular Speed").build(
                 ui,
                 &mut app_ui.borrow_mut().ground.config.ants.angular_speed,
             );
 
-            Drag::new("Ant Vision Range").build(
+            Drag::new("Ant lose energy after rounds")
+                .range(1, u32::MAX)
+                .build(
+                    ui,
+                    &mut app_ui.borrow_mut().ground.config.ants.energy_loss_rounds,
+                );
+
+            Drag::new("Ant Lifespan")
+                .range(1, u32::MAX)
+                .build(ui, &mut app_ui.borrow_mut().ground.config.ants.lifespan);
+
+            for caste in Caste::ALL {
+                ui.text(format!("{}s", caste));
+                let mut app = app_ui.borrow_mut();
+                let mouth_reach = app.ground.config.ants.mouth_reach;
+                let stats = app.ground.config.ants.caste_mut(caste);
+
+                Drag::new(format!("{} Speed", caste))
+                    .range(0.01, f32::MAX)
+                    .build(ui, &mut stats.speed);
+                Drag::new(format!("{} Vision Range", caste))
+                    .range(mouth_reach, f32::MAX)
+                    .build(ui, &mut stats.vision_range);
+                Drag::new(format!("{} Carry Capacity", caste)).build(ui, &mut stats.carry_capacity);
+                Drag::new(format!("{} Energy Cost", caste)).build(ui, &mut stats.energy_cost);
+                Drag::new(format!("{} Energy Loss Amount", caste))
+                    .build(ui, &mut stats.energy_loss_amount);
+            }
+
+            ui.separator();
+            ui.text_colored(helper::RED.get_data(), "Nests");
+
+            Drag::new("Colonies")
+                .range(1, 16)
+                .build(ui, &mut app_ui.borrow_mut().ground.config.nests.colonies);
+
+            {
+                let mut app = app_ui.borrow_mut();
+                let nests = &mut app.ground.config.nests;
+                Drag::new("Max Energy")
+                    .range(nests.start_energy.max(1), u32::MAX)
+                    .build(ui, &mut nests.max_energy);
+            }
+
+            Drag::new("Spawn Cost")
+                .build(ui, &mut app_ui.borrow_mut().ground.config.nests.spawn_cost);
+
+            Drag::new("Min Reserve")
+                .build(ui, &mut app_ui.borrow_mut().ground.config.nests.min_reserve);
+
+            Drag::new("Max Population (0 = no limit)").build(
                 ui,
-                &mut (app_ui.borrow_mut()).ground.config.ants.vision_range,
+                &mut app_ui.borrow_mut().ground.config.nests.max_population,
             );
 
-            Drag::new("Ant Energy Loss Amount").build(
+            Drag::new("Granary Capacity").build(
                 ui,
-                &mut app_ui.borrow_mut().ground.config.ants.energy_loss_amount,
+                &mut app_ui.borrow_mut().ground.config.nests.granary_capacity,
             );
 
-            Drag::new("Ant lose energy after rounds").build(
+            Drag::new("Granary Loss Amount").build(
                 ui,
-                &mut app_ui.borrow_mut().ground.config.ants.energy_loss_rounds,
+                &mut app_ui.borrow_mut().ground.config.nests.granary_loss_amount,
             );
 
+            Drag::new("Granary lose energy after rounds")
+                .range(1, u32::MAX)
+                .build(
+                    ui,
+                    &mut app_ui.borrow_mut().ground.config.nests.granary_loss_rounds,
+                );
+
+            Drag::new("Found Cost")
+                .build(ui, &mut app_ui.borrow_mut().ground.config.nests.found_cost);
+
+            Drag::new("Found Range")
+                .range(0.0, f32::MAX)
+                .build(ui, &mut app_ui.borrow_mut().ground.config.nests.found_range);
+
+            {
+                let mut app = app_ui.borrow_mut();
+                let size = app.ground.config.world.size();
+                placement_control(ui, "Nest", &mut app.ground.config.nests.placement, size);
+            }
+
             ui.separator();
-            ui.text_colored(helper::RED.get_data(), "Nests");
+            ui.text_colored(helper::RED.get_data(), "Pheromones");
+
+            Drag::new("Evaporation").speed(0.001).range(0.0, 1.0).build(
+                ui,
+                &mut app_ui.borrow_mut().ground.config.pheromones.evaporation,
+            );
+
+            Drag::new("Diffusion").speed(0.001).range(0.0, 1.0).build(
+                ui,
+                &mut app_ui.borrow_mut().ground.config.pheromones.diffusion,
+            );
 
-            Drag::new("Max Energy")
-                .build(ui, &mut app_ui.borrow_mut().ground.config.nests.max_energy);
+            Drag::new("Sense Distance").build(
+                ui,
+                &mut app_ui.borrow_mut().ground.config.pheromones.sense_distance,
+            );
+
+            Drag::new("Max Deposit").build(
+                ui,
+                &mut app_ui.borrow_mut().ground.config.pheromones.max_deposit,
+            );
+
+            ui.separator();
+            ui.text_colored(helper::RED.get_data(), "Score Weights");
+
+            Drag::new("Food Delivered").speed(0.01).build(
+                ui,
+                &mut app_ui.borrow_mut().ground.config.score.food_delivered,
+            );
+
+            Drag::new("Ants Spawned").speed(0.01).build(
+                ui,
+                &mut app_ui.borrow_mut().ground.config.score.ants_spawned,
+            );
+
+            Drag::new("Ant Ticks")
+                .speed(0.001)
+                .build(ui, &mut app_ui.borrow_mut().ground.config.score.ant_ticks);
+
+            Drag::new("Nest Energy").speed(0.001).build(
+                ui,
+                &mut app_ui.borrow_mut().ground.config.score.energy_integral,
+            );
+
+            Drag::new("Survival Ticks").speed(0.01).build(
+                ui,
+                &mut app_ui.borrow_mut().ground.config.score.survival_ticks,
+            );
+
+            let mut app = app_ui.borrow_mut();
+            if let Err(e) = app.ground.config.validate() {
+                app.ground.config = last_valid;
+                app.config_message = Some(format!("Not applied: {}", e));
+            }
         });
 }
 
@@ -107,13 +403,19 @@ pub fn statistics(ui: &mut Ui, app_ui: &Rc<RefCell<Simulator>>) {
         .build(ui, || {
             ui.text(format!("Num Ants: {}", app_ui.borrow().ground.num_ants()));
 
-            ui.columns(3, "Ant View", true);
+            ui.columns(6, "Ant View", true);
             ui.text("Ant");
             ui.next_column();
+            ui.text("Colony");
+            ui.next_column();
+            ui.text("Caste");
+            ui.next_column();
             ui.text("Food");
             ui.next_column();
             ui.text("Carrying");
             ui.next_column();
+            ui.text("Age");
+            ui.next_column();
             ui.columns(1, "Main", false);
 
             ChildWindow::new("AntList")
@@ -121,14 +423,20 @@ pub fn statistics(ui: &mut Ui, app_ui: &Rc<RefCell<Simulator>>) {
                 .border(true)
                 .scroll_bar(true)
                 .build(ui, || {
-                    ui.columns(3, "AntList_Inner", true);
+                    ui.columns(6, "AntList_Inner", true);
 
-                    for ant_drawable in app_ui.borrow().ground.ant_list() {
-                        ui.text(ant_drawable.ant.id.to_string());
+                    for ant in app_ui.borrow().ground.ant_list() {
+                        ui.text(ant.id.to_string());
+                        ui.next_column();
+                        ui.text(ant.colony.to_string());
                         ui.next_column();
-                        ui.text(ant_drawable.ant.energy.to_string());
+                        ui.text(ant.caste.to_string());
                         ui.next_column();
-                        ui.text(ant_drawable.ant.carrying.to_string());
+                        ui.text(ant.energy.to_string());
+                        ui.next_column();
+                        ui.text(ant.carrying.to_string());
+                        ui.next_column();
+                        ui.text(ant.age.to_string());
                         ui.next_column();
                     }
 
@@ -137,11 +445,17 @@ pub fn statistics(ui: &mut Ui, app_ui: &Rc<RefCell<Simulator>>) {
 
             ui.text(format!("Num Foods: {}", app_ui.borrow().ground.num_foods()));
 
-            ui.columns(2, "Nest View", true);
+            ui.columns(5, "Nest View", true);
             ui.text("Nest");
             ui.next_column();
+            ui.text("Colony");
+            ui.next_column();
             ui.text("Energy");
             ui.next_column();
+            ui.text("Granary");
+            ui.next_column();
+            ui.text("Spawn Denied");
+            ui.next_column();
             ui.columns(1, "Main", false);
 
             ChildWindow::new("NestList")
@@ -149,16 +463,91 @@ pub fn statistics(ui: &mut Ui, app_ui: &Rc<RefCell<Simulator>>) {
                 .border(true)
                 .scroll_bar(true)
                 .build(ui, || {
-                    ui.columns(2, "NestList_Inner", true);
+                    ui.columns(5, "NestList_Inner", true);
 
-                    for nest_drawable in app_ui.borrow().ground.nest_list() {
-                        ui.text(nest_drawable.nest.id.to_string());
+                    for nest in app_ui.borrow().ground.nest_list() {
+                        ui.text(nest.id.to_string());
+                        ui.next_column();
+                        ui.text(nest.colony.to_string());
+                        ui.next_column();
+                        ui.text(nest.energy.to_string());
+                        ui.next_column();
+                        ui.text(nest.granary.to_string());
                         ui.next_column();
-                        ui.text(nest_drawable.nest.energy.to_string());
+                        ui.text(nest.spawn_denied.to_string());
                         ui.next_column();
                     }
 
                     ui.columns(1, "AntList_Inner", true);
                 });
+
+            ui.text(format!("Round Tick: {}", app_ui.borrow().ground.tick()));
+
+            ui.columns(4, "Score View", true);
+            ui.text("Colony");
+            ui.next_column();
+            ui.text("Food");
+            ui.next_column();
+            ui.text("Spawned");
+            ui.next_column();
+            ui.text("Fitness");
+            ui.next_column();
+            ui.columns(1, "Main", false);
+
+            ChildWindow::new("ScoreList")
+                .size([250.0, 100.0])
+                .border(true)
+                .scroll_bar(true)
+                .build(ui, || {
+                    ui.columns(4, "ScoreList_Inner", true);
+
+                    let app = app_ui.borrow();
+                    for score in app.ground.score().colonies() {
+                        ui.text(score.colony.to_string());
+                        ui.next_column();
+                        ui.text(score.food_delivered.to_string());
+                        ui.next_column();
+                        ui.text(score.ants_spawned.to_string());
+                        ui.next_column();
+                        ui.text(format!("{:.1}", score.fitness(&app.ground.config.score)));
+                        ui.next_column();
+                    }
+
+                    ui.columns(1, "ScoreList_Inner", true);
+                });
+
+            ui.text(format!(
+                "Deaths: {}",
+                app_ui.borrow().ground.deaths().deaths().len()
+            ));
+
+            ui.columns(3, "Death View", true);
+            ui.text("Colony");
+            ui.next_column();
+            for cause in DeathCause::ALL {
+                ui.text(cause.to_string());
+                ui.next_column();
+            }
+            ui.columns(1, "Main", false);
+
+            ChildWindow::new("DeathList")
+                .size([250.0, 100.0])
+                .border(true)
+                .scroll_bar(true)
+                .build(ui, || {
+                    ui.columns(3, "DeathList_Inner", true);
+
+                    let app = app_ui.borrow();
+                    for score in app.ground.score().colonies() {
+                        ui.text(score.colony.to_string());
+                        ui.next_column();
+                        for cause in DeathCause::ALL {
+                            ui.text(app.ground.deaths().count(score.colony, cause).to_string());
+                            ui.next_column();
+                        }
+                    }
+
+                    ui.columns(1, "DeathList_Inner", true);
+                });
         });
 }
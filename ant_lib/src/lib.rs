@@ -1,16 +1,15 @@
-use std::{collections::HashMap, sync::Mutex};
-
 use common::{
-    animals::ant::{Ant, AntAction},
-    buildings::{Nest, NestAction},
-    helper::{SeenObject, Vector2D, Vision},
+    animals::{
+        ant::{Ant, AntAction, Caste},
+        context::AntContext,
+    },
+    buildings::{context::NestContext, Nest, NestAction},
+    helper::{SeenObject, Vector2D},
     items::food::FoodPellet,
+    pheromones::PheromoneKind,
 };
-use rand::Rng;
-
-#[macro_use]
-extern crate lazy_static;
 
+#[repr(u8)]
 #[derive(Clone, Copy)]
 enum AntObjective {
     Searching,
@@ -19,56 +18,48 @@ enum AntObjective {
     BringingFoodHome,
 }
 
-struct AntMemory {
+/// Lives in the memory block the host keeps for every ant.
+/// A new ant starts with all bytes zero, which means searching without knowing the nest.
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct AntState {
     objective: AntObjective,
-    nest_pos: Option<Vector2D>,
-}
-
-impl AntMemory {
-    fn new() -> AntMemory {
-        AntMemory {
-            objective: AntObjective::Searching,
-            nest_pos: None,
-        }
-    }
-}
-
-impl Default for AntMemory {
-    fn default() -> Self {
-        AntMemory::new()
-    }
+    knows_nest: bool,
+    nest_pos: Vector2D,
 }
 
-lazy_static! {
-    static ref FRAMECOUNTER: std::sync::Mutex<usize> = Mutex::new(0);
-    static ref ANTMEMORY: std::sync::Mutex<HashMap<usize, AntMemory>> = Mutex::new(HashMap::new());
-}
+common::export_abi!();
 
-fn switch_to_objective(ant: &usize, objective: AntObjective) {
-    ANTMEMORY.lock().unwrap().get_mut(ant).unwrap().objective = objective;
+#[no_mangle]
+pub extern "C" fn ant_memory_size() -> usize {
+    std::mem::size_of::<AntState>()
 }
 
 #[no_mangle]
-pub extern "C" fn ant_update(ant: &Ant, vision: &Vec<Vision>) -> AntAction {
+pub extern "C" fn ant_update(ant: &Ant, context: &mut AntContext) -> AntAction {
     let mut closest_food: Option<FoodPellet> = None;
-    let mut min_dist = std::f32::MAX;
-
-    if ANTMEMORY
-        .lock()
-        .unwrap()
-        .entry(ant.id)
-        .or_default()
-        .nest_pos
-        .is_none()
-    {
+    let mut min_dist = f32::MAX;
+
+    let vision = &context.vision;
+    let pheromones = &context.pheromones;
+    let rng = &mut context.rng;
+
+    // All zero is a valid AntState
+    let state = match unsafe { context.memory.get_mut::<AntState>() } {
+        Some(state) => state,
+        None => return AntAction::Nothing,
+    };
+
+    if !state.knows_nest {
         // This is the ants first update tick, it has not moved, so it is at the nest
         // The ant can remember this position as the nest position
-        ANTMEMORY
-            .lock()
-            .unwrap()
-            .entry(ant.id)
-            .or_default()
-            .nest_pos = Some(ant.position);
+        state.knows_nest = true;
+        state.nest_pos = ant.position;
+    }
+
+    if context.recalled {
+        // Whatever the ant was doing, it heads home
+        state.objective = AntObjective::BringingFoodHome;
     }
 
     loop {
@@ -87,20 +78,25 @@ pub extern "C" fn ant_update(ant: &Ant, vision: &Vec<Vision>) -> AntAction {
             }
         }
 
-        let current_objective = ANTMEMORY
-            .lock()
-            .unwrap()
-            .entry(ant.id)
-            .or_default()
-            .objective;
+        let current_objective = state.objective;
 
         match current_objective {
             AntObjective::Searching => {
                 if closest_food.is_some() {
-                    switch_to_objective(&ant.id, AntObjective::GoToFood);
+                    state.objective = AntObjective::GoToFood;
                 } else {
+                    // Follow a food trail if there is one
+                    let trail = pheromones.get(PheromoneKind::Food);
+                    if trail.left.max(trail.right) > 2.0 * trail.center.max(0.1) {
+                        if trail.left > trail.right {
+                            return AntAction::RotateLeft(ant.angular_speed);
+                        } else {
+                            return AntAction::RotateRight(ant.angular_speed);
+                        }
+                    }
+
                     // Move randomly around
-                    match rand::thread_rng().gen_range::<u8>(0, 7) {
+                    match rng.gen_range(0, 7) {
                         0 => return AntAction::RotateLeft(90.0),
                         1 => return AntAction::RotateRight(90.0),
                         2 => return AntAction::GoForward(100.0),
@@ -115,7 +111,7 @@ pub extern "C" fn ant_update(ant: &Ant, vision: &Vec<Vision>) -> AntAction {
                 if let Some(food) = closest_food {
                     if min_dist < ant.mouth_reach {
                         // Ant is at Food
-                        switch_to_objective(&ant.id, AntObjective::Eating);
+                        state.objective = AntObjective::Eating;
                     } else {
                         // Go To Food
                         let food_pos = food.get_position();
@@ -140,43 +136,35 @@ pub extern "C" fn ant_update(ant: &Ant, vision: &Vec<Vision>) -> AntAction {
                         }
                     }
                 } else {
-                    switch_to_objective(&ant.id, AntObjective::Searching);
+                    state.objective = AntObjective::Searching;
                 }
             }
             AntObjective::Eating => {
                 // Eat and if satiated decide what to do next
                 if let Some(food) = closest_food {
                     if ant.energy >= (ant.max_energy - food.bite_size) {
-                        switch_to_objective(&ant.id, AntObjective::BringingFoodHome);
+                        state.objective = AntObjective::BringingFoodHome;
 
                         return AntAction::CarryFood(food);
                     } else {
                         return AntAction::EatFood(food);
                     }
                 } else {
-                    switch_to_objective(&ant.id, AntObjective::Searching);
+                    state.objective = AntObjective::Searching;
                 }
             }
             AntObjective::BringingFoodHome => {
                 if ant.energy < ant.max_energy / 5 {
                     // We need to eat some of our carried food
-                    switch_to_objective(&ant.id, AntObjective::Eating);
+                    state.objective = AntObjective::Eating;
                     return AntAction::UnloadFood;
                 }
 
-                let nest_option = ANTMEMORY
-                    .lock()
-                    .unwrap()
-                    .entry(ant.id)
-                    .or_default()
-                    .nest_pos;
-
-                if let Some(nest_pos) = nest_option {
+                if state.knows_nest {
+                    let nest_pos = state.nest_pos;
                     if ant.position.distance(nest_pos) <= ant.mouth_reach {
                         // We are there, we can unload at the nest and continue searching
-                        println!("Ant wants to unload.");
-                        switch_to_objective(&ant.id, AntObjective::Searching);
-                        println!("Returning UnloadFood");
+                        state.objective = AntObjective::Searching;
                         return AntAction::UnloadFood;
                     }
 
@@ -194,6 +182,9 @@ pub extern "C" fn ant_update(ant: &Ant, vision: &Vec<Vision>) -> AntAction {
                         return AntAction::RotateLeft(angle.abs());
                     } else if angle < -0.5 * ant.angular_speed {
                         return AntAction::RotateRight(angle.abs());
+                    } else if rng.gen_range(0, 4) == 0 {
+                        // Leave a trail so others can find the food
+                        return AntAction::DepositPheromone(PheromoneKind::Food, 10.0);
                     } else {
                         return AntAction::GoForward(100.0);
                     }
@@ -206,22 +197,19 @@ pub extern "C" fn ant_update(ant: &Ant, vision: &Vec<Vision>) -> AntAction {
 }
 
 #[no_mangle]
-pub extern "C" fn nest_update(nest: &Nest) -> NestAction {
+pub extern "C" fn nest_update(nest: &Nest, context: &mut NestContext) -> NestAction {
     /*
     // for debugging, only spawn a single ant ever
-    if *FRAMECOUNTER.lock().unwrap() == 0 {
-        *FRAMECOUNTER.lock().unwrap() += 1;
-        NestAction::SpawnAnts(1)
+    if context.tick == 0 {
+        NestAction::SpawnAnts(1, Caste::Worker)
     } else {
         NestAction::Nothing
     }
     */
 
-    if nest.energy > 1000 && *FRAMECOUNTER.lock().unwrap() >= 60 {
-        *FRAMECOUNTER.lock().unwrap() = 0;
-        NestAction::SpawnAnts(1)
+    if nest.energy > 1000 && context.tick % 60 == 59 {
+        NestAction::SpawnAnts(1, Caste::Worker)
     } else {
-        *FRAMECOUNTER.lock().unwrap() += 1;
         NestAction::Nothing
     }
 }
@@ -229,7 +217,4 @@ pub extern "C" fn nest_update(nest: &Nest) -> NestAction {
 #[no_mangle]
 pub extern "C" fn reset() {
     println!("Resetting");
-    *FRAMECOUNTER.lock().unwrap() = 0;
-
-    ANTMEMORY.lock().unwrap().clear();
 }
@@ -0,0 +1,140 @@
+//! Compares building the vision of every ant by scanning all ants and food
+//! with the queries of the spatial grid.
+//!
+//!     cargo bench -p ant_sim
+
+use std::time::{Duration, Instant};
+
+use ant_sim::spatial::SpatialGrid;
+use common::animals::ant::{Ant, Caste};
+use common::helper::config::Config;
+use common::helper::{SimRng, Vector2D};
+use common::items::food::FoodPellet;
+
+const ROUNDS: u32 = 5;
+
+struct World {
+    size: Vector2D,
+    ants: Vec<Ant>,
+    food: Vec<FoodPellet>,
+}
+
+/// Ants and food spread uniformly, the ground grows with the number of ants so the density stays the same
+fn world(num_ants: usize, config: &Config) -> World {
+    let side = 1000.0 * (num_ants as f32 / 1000.0).sqrt();
+    let size = Vector2D::new(side, side);
+    let mut rng = SimRng::new(num_ants as u64);
+    let mut random_pos = || Vector2D::new(rng.gen_f32() * side, rng.gen_f32() * side);
+
+    let ants = (0..num_ants)
+        .map(|id| Ant::new_at_pos(id, 0, 0, &config.ants, Caste::Worker, random_pos()))
+        .collect();
+    let food = (0..num_ants / 2)
+        .map(|id| {
+            FoodPellet::new_at_pos(
+                id,
+                random_pos(),
+                config.food.nutrition,
+                config.food.eaten_value,
+            )
+        })
+        .collect();
+
+    World { size, ants, food }
+}
+
+/// Collects what every ant sees like the ground did before it had a grid.
+/// Returns the number of seen objects, so the work can not be optimized away.
+fn linear_scan(world: &World, range: f32) -> usize {
+    let mut seen = 0;
+    let mut found = Vec::new();
+    for (i, ant) in world.ants.iter().enumerate() {
+        found.clear();
+        for (j, other) in world.ants.iter().enumerate() {
+            if i != j && ant.position.distance(other.position) < range {
+                found.push(j);
+            }
+        }
+        seen += found.len();
+
+        found.clear();
+        for (j, food) in world.food.iter().enumerate() {
+            if ant.position.distance(food.get_position()) < range {
+                found.push(j);
+            }
+        }
+        seen += found.len();
+    }
+    seen
+}
+
+fn grid_queries(world: &World, range: f32) -> usize {
+    let mut grid = SpatialGrid::new(world.size, range);
+    grid.rebuild(&world.ants, &world.food);
+
+    let mut seen = 0;
+    let mut found = Vec::new();
+    for (i, ant) in world.ants.iter().enumerate() {
+        grid.ants_within(ant.position, range, &mut found);
+        seen += found.iter().filter(|&&j| j != i).count();
+        grid.food_within(ant.position, range, &mut found);
+        seen += found.len();
+    }
+    seen
+}
+
+/// Panics unless the grid finds exactly the ants and food the scan finds, for every ant
+fn check_same_vision(world: &World, range: f32) {
+    let mut grid = SpatialGrid::new(world.size, range);
+    grid.rebuild(&world.ants, &world.food);
+
+    let mut found = Vec::new();
+    for ant in &world.ants {
+        let scan: Vec<usize> = (0..world.ants.len())
+            .filter(|&j| ant.position.distance(world.ants[j].position) < range)
+            .collect();
+        grid.ants_within(ant.position, range, &mut found);
+        assert_eq!(found, scan, "grid and scan disagree on ants");
+
+        let scan: Vec<usize> = (0..world.food.len())
+            .filter(|&j| ant.position.distance(world.food[j].get_position()) < range)
+            .collect();
+        grid.food_within(ant.position, range, &mut found);
+        assert_eq!(found, scan, "grid and scan disagree on food");
+    }
+}
+
+fn time(f: impl Fn() -> usize) -> (Duration, usize) {
+    let start = Instant::now();
+    let mut seen = 0;
+    for _ in 0..ROUNDS {
+        seen = f();
+    }
+    (start.elapsed() / ROUNDS, seen)
+}
+
+fn main() {
+    let config = Config::new();
+
+    for &(name, range) in &[
+        ("vision_range", config.ants.worker.vision_range),
+        ("mouth_reach", config.ants.mouth_reach),
+    ] {
+        for num_ants in [1_000, 10_000] {
+            let world = world(num_ants, &config);
+            check_same_vision(&world, range);
+            let (linear, linear_seen) = time(|| linear_scan(&world, range));
+            let (grid, grid_seen) = time(|| grid_queries(&world, range));
+            assert_eq!(linear_seen, grid_seen, "grid and scan disagree");
+
+            println!(
+                "{:>6} ants, {:<12} linear {:>10.3?}  grid {:>10.3?}  speedup {:>6.1}x",
+                num_ants,
+                name,
+                linear,
+                grid,
+                linear.as_secs_f64() / grid.as_secs_f64()
+            );
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use std::fmt;
+
+use common::animals::ant::Ant;
+use serde::{Deserialize, Serialize};
+
+/// Why an ant died
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DeathCause {
+    /// Ran out of energy
+    Starvation,
+    /// Reached its lifespan
+    OldAge,
+}
+
+impl DeathCause {
+    pub const COUNT: usize = 2;
+    pub const ALL: [DeathCause; DeathCause::COUNT] = [DeathCause::Starvation, DeathCause::OldAge];
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    /// None while the ant is alive. An ant starving on its last day counts as starved.
+    pub fn of(ant: &Ant) -> Option<DeathCause> {
+        if ant.energy == 0 {
+            Some(DeathCause::Starvation)
+        } else if ant.is_too_old() {
+            Some(DeathCause::OldAge)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for DeathCause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeathCause::Starvation => write!(f, "Starvation"),
+            DeathCause::OldAge => write!(f, "Old age"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Death {
+    pub tick: usize,
+    pub ant_id: usize,
+    pub colony: usize,
+    pub age: u32,
+    pub cause: DeathCause,
+}
+
+/// Every ant that died in the current round, oldest first
+#[derive(Default, Serialize, Deserialize)]
+pub struct DeathLog {
+    deaths: Vec<Death>,
+}
+
+impl DeathLog {
+    pub fn new() -> DeathLog {
+        DeathLog::default()
+    }
+
+    pub fn clear(&mut self) {
+        self.deaths.clear();
+    }
+
+    pub fn record(&mut self, death: Death) {
+        self.deaths.push(death);
+    }
+
+    pub fn deaths(&self) -> &[Death] {
+        &self.deaths
+    }
+
+    /// Number of ants of `colony` that died of `cause`
+    pub fn count(&self, colony: usize, cause: DeathCause) -> usize {
+        self.deaths
+            .iter()
+            .filter(|d| d.colony == colony && d.cause == cause)
+            .count()
+    }
+}
@@ -0,0 +1,206 @@
+use common::animals::ant::Ant;
+use common::items::food::{FoodAction, FoodOutcome, FoodPellet};
+
+use crate::ledger::{EnergyFlow, EnergyLedger};
+
+/// An `EatFood` or `CarryFood` of one ant, collected while the actions of a tick are applied
+pub struct FoodRequest {
+    /// Index of the ant in the ant list
+    pub ant: usize,
+    pub food_id: usize,
+    pub action: FoodAction,
+}
+
+/// Food ids are handed out in ascending order and the list is never reordered
+fn food_index(food: &[FoodPellet], id: usize) -> Option<usize> {
+    food.binary_search_by_key(&id, |f| f.id).ok()
+}
+
+/// Serves all food requests of a tick and writes what every ant got into `outcomes`.
+///
+/// `requests` have to be in the order of the ant list, which is the spawn order, so older ants
+/// are served first. No pellet gives away more nutrition than it has.
+pub fn resolve(
+    requests: &[FoodRequest],
+    ants: &mut [Ant],
+    food: &mut [FoodPellet],
+    outcomes: &mut [FoodOutcome],
+    ledger: &mut EnergyLedger,
+) {
+    // What every ant wants is decided before anyone is served, so it does not depend on the order
+    for request in requests {
+        let ant = &ants[request.ant];
+        let requested = match food_index(food, request.food_id) {
+            Some(j) => match request.action {
+                FoodAction::Eat => ant.bite_wanted(&food[j]),
+                FoodAction::Carry => ant.carry_wanted(&food[j]),
+                FoodAction::None => 0,
+            },
+            // The pellet is already gone
+            None => 0,
+        };
+
+        outcomes[request.ant] = FoodOutcome {
+            action: request.action,
+            food_id: request.food_id,
+            requested,
+            received: 0,
+        };
+    }
+
+    for request in requests {
+        if let Some(j) = food_index(food, request.food_id) {
+            let ant = &mut ants[request.ant];
+            let (flow, received) = match request.action {
+                FoodAction::Eat => (EnergyFlow::Eaten, ant.eat_food(&mut food[j])),
+                FoodAction::Carry => (EnergyFlow::PickedUp, ant.carry_food(&mut food[j])),
+                FoodAction::None => continue,
+            };
+            ledger.record(flow, received);
+            outcomes[request.ant].received = received;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::animals::ant::Caste;
+    use common::helper::config::AntConfig;
+    use common::helper::Vector2D;
+
+    const PELLET_ID: usize = 7;
+
+    fn hungry_ant(id: usize) -> Ant {
+        let origin = Vector2D::new(0.0, 0.0);
+        let mut ant = Ant::new_at_pos(id, 0, 0, &AntConfig::new(), Caste::Worker, origin);
+        ant.energy = 1;
+        ant
+    }
+
+    /// Every ant takes `actions[i]` on one pellet, returns the outcomes the ants get next tick
+    fn serve(
+        ants: &mut [Ant],
+        pellet: &mut FoodPellet,
+        actions: &[FoodAction],
+        ledger: &mut EnergyLedger,
+    ) -> Vec<FoodOutcome> {
+        let requests: Vec<FoodRequest> = actions
+            .iter()
+            .enumerate()
+            .map(|(ant, &action)| FoodRequest {
+                ant,
+                food_id: PELLET_ID,
+                action,
+            })
+            .collect();
+        let mut outcomes = vec![FoodOutcome::default(); ants.len()];
+        resolve(
+            &requests,
+            ants,
+            std::slice::from_mut(pellet),
+            &mut outcomes,
+            ledger,
+        );
+        outcomes
+    }
+
+    fn handed_out(outcomes: &[FoodOutcome]) -> u32 {
+        outcomes.iter().map(|outcome| outcome.received).sum()
+    }
+
+    #[test]
+    fn two_ants_eating_the_same_pellet_share_it() {
+        let mut ants = [hungry_ant(0), hungry_ant(1)];
+        let mut pellet = FoodPellet::new_at_pos(PELLET_ID, Vector2D::new(0.0, 0.0), 30, 20);
+        let mut ledger = EnergyLedger::new();
+
+        let outcomes = serve(
+            &mut ants,
+            &mut pellet,
+            &[FoodAction::Eat, FoodAction::Eat],
+            &mut ledger,
+        );
+
+        // The older ant is served first, both asked for a full bite
+        assert_eq!((outcomes[0].requested, outcomes[0].received), (20, 20));
+        assert_eq!((outcomes[1].requested, outcomes[1].received), (20, 10));
+        assert!(outcomes
+            .iter()
+            .all(|o| o.action == FoodAction::Eat && o.food_id == PELLET_ID));
+        assert_eq!(handed_out(&outcomes), 30);
+        assert_eq!(pellet.nutrition, 0);
+        assert_eq!((ants[0].energy, ants[1].energy), (21, 11));
+        assert_eq!(ledger.get(EnergyFlow::Eaten), 30);
+        assert_eq!(ledger.net_change(), 0);
+    }
+
+    #[test]
+    fn an_eater_and_a_carrier_share_a_pellet() {
+        let mut ants = [hungry_ant(0), hungry_ant(1)];
+        let mut pellet = FoodPellet::new_at_pos(PELLET_ID, Vector2D::new(0.0, 0.0), 30, 20);
+        let mut ledger = EnergyLedger::new();
+
+        let outcomes = serve(
+            &mut ants,
+            &mut pellet,
+            &[FoodAction::Eat, FoodAction::Carry],
+            &mut ledger,
+        );
+
+        assert_eq!(outcomes[0].action, FoodAction::Eat);
+        assert_eq!(outcomes[0].received, 20);
+        assert_eq!(outcomes[1].action, FoodAction::Carry);
+        assert_eq!((outcomes[1].requested, outcomes[1].received), (30, 10));
+        assert_eq!(handed_out(&outcomes), 30);
+        assert_eq!(pellet.nutrition, 0);
+        assert_eq!((ants[0].energy, ants[1].carrying), (21, 10));
+        assert_eq!(ledger.get(EnergyFlow::Eaten), 20);
+        assert_eq!(ledger.get(EnergyFlow::PickedUp), 10);
+    }
+
+    #[test]
+    fn a_small_pellet_gives_no_more_than_it_has() {
+        let mut ants = [hungry_ant(0), hungry_ant(1), hungry_ant(2)];
+        let mut pellet = FoodPellet::new_at_pos(PELLET_ID, Vector2D::new(0.0, 0.0), 5, 20);
+        let mut ledger = EnergyLedger::new();
+
+        let outcomes = serve(
+            &mut ants,
+            &mut pellet,
+            &[FoodAction::Carry, FoodAction::Eat, FoodAction::None],
+            &mut ledger,
+        );
+
+        assert_eq!((outcomes[0].requested, outcomes[0].received), (5, 5));
+        assert_eq!((outcomes[1].requested, outcomes[1].received), (5, 0));
+        // An ant without a food action gets an empty outcome
+        assert_eq!(outcomes[2].action, FoodAction::None);
+        assert_eq!(outcomes[2].received, 0);
+        assert_eq!(handed_out(&outcomes), 5);
+        assert_eq!(pellet.nutrition, 0);
+        assert_eq!(ants[1].energy, 1);
+        assert_eq!(
+            ledger.get(EnergyFlow::Eaten) + ledger.get(EnergyFlow::PickedUp),
+            5
+        );
+    }
+
+    #[test]
+    fn a_pellet_that_is_gone_gives_nothing() {
+        let mut ants = [hungry_ant(0)];
+        let mut outcomes = vec![FoodOutcome::default()];
+        let mut ledger = EnergyLedger::new();
+        let requests = [FoodRequest {
+            ant: 0,
+            food_id: PELLET_ID,
+            action: FoodAction::Eat,
+        }];
+
+        resolve(&requests, &mut ants, &mut [], &mut outcomes, &mut ledger);
+
+        assert_eq!(outcomes[0].action, FoodAction::Eat);
+        assert_eq!((outcomes[0].requested, outcomes[0].received), (0, 0));
+        assert_eq!(ledger.get(EnergyFlow::Eaten), 0);
+    }
+}
@@ -0,0 +1,1276 @@
+use crate::deaths::{Death, DeathCause, DeathLog};
+use crate::feeding::{self, FoodRequest};
+use crate::ledger::{EnergyFlow, EnergyLedger};
+use crate::memory::MemoryBlock;
+use crate::persist::{self, PersistError};
+use crate::pheromones::PheromoneField;
+use crate::placement::{self, Surroundings};
+use crate::replay::{Replay, ReplayError, TickRecord};
+use crate::score::Scoreboard;
+use crate::sources::FoodSource;
+use crate::spatial::SpatialGrid;
+use crate::ColonyBehavior;
+use common::animals::ant::{Ant, AntAction, Caste};
+use common::animals::context::AntContext;
+use common::buildings::context::NestContext;
+use common::buildings::{Nest, NestAction, SpawnDenial};
+use common::helper::*;
+use common::items::food::{FoodAction, FoodOutcome, FoodPellet};
+use common::terrain::Obstacle;
+
+use config::{Config, FoodSourceConfig};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
+
+const ANT_RNG_STREAM: u64 = 0;
+const NEST_RNG_STREAM: u64 = 1;
+
+/// Where the nests and ants get their decisions from in one tick
+enum Decisions<'a> {
+    Plugins(&'a [ColonyBehavior]),
+    Recorded(&'a TickRecord),
+}
+
+impl Decisions<'_> {
+    /// Bytes of plugin memory a new ant of `colony` gets, replays do not need any
+    fn memory_size(&self, colony: usize) -> usize {
+        match self {
+            Decisions::Plugins(behaviors) => {
+                ColonyBehavior::for_colony(behaviors, colony).memory_size
+            }
+            Decisions::Recorded(_) => 0,
+        }
+    }
+}
+
+/// The simulated world. Everything but caches is saved in snapshots, see [`Ground::save`].
+#[derive(Serialize, Deserialize)]
+pub struct Ground {
+    size: Vector2D,
+    food: Vec<FoodPellet>,
+    ants: Vec<Ant>,
+    /// Plugin memory of every ant, same order as `ants`
+    ant_memory: Vec<MemoryBlock>,
+    /// What every ant got from its food action last tick, same order as `ants`
+    food_outcomes: Vec<FoodOutcome>,
+    nests: Vec<Nest>,
+    pheromones: PheromoneField,
+    /// Index for vision queries, rebuilt before the ants decide
+    #[serde(skip, default = "SpatialGrid::empty")]
+    grid: SpatialGrid,
+    food_timer: i32,
+    /// Centers of clustered food, see [`placement::patch_centers`]
+    #[serde(default)]
+    food_patches: Vec<Vector2D>,
+    #[serde(default)]
+    obstacles: Vec<Obstacle>,
+    #[serde(default)]
+    food_sources: Vec<FoodSource>,
+
+    pub config: Config,
+
+    #[serde(skip)]
+    new_round_pending: bool,
+
+    // statistics
+    tick: usize,
+    score: Scoreboard,
+    #[serde(default)]
+    deaths: DeathLog,
+    /// Energy flows of the last tick
+    #[serde(skip)]
+    ledger: EnergyLedger,
+    /// Ids of the nests that recalled their ants in this tick
+    #[serde(skip)]
+    recalled_nests: Vec<usize>,
+    /// Colony and signal of every broadcast in this tick
+    #[serde(skip)]
+    signals: Vec<(usize, u32)>,
+    #[serde(skip)]
+    record_replay: bool,
+    #[serde(skip)]
+    replay: Option<Replay>,
+
+    // technical
+    next_food_id: usize,
+    /// Satellite nests take their id from here as well, so this is not the number of colonies
+    #[serde(alias = "next_colony_id")]
+    next_nest_id: usize,
+    next_ant_id: usize,
+    seed: u64,
+    rng: SimRng,
+}
+
+impl Ground {
+    /// A ground of the size in `config.world`, the first round starts with the first update
+    pub fn new(config: Config) -> Ground {
+        let size = config.world.size();
+
+        Ground {
+            food: Vec::new(),
+            ants: Vec::new(),
+            ant_memory: Vec::new(),
+            food_outcomes: Vec::new(),
+            nests: Vec::new(),
+            pheromones: PheromoneField::new(size, config.pheromones.cell_size),
+            grid: SpatialGrid::new(size, config.ants.max_vision_range()),
+            size,
+            food_timer: config.food.spawn_time,
+            food_patches: Vec::new(),
+            obstacles: config.world.obstacles.clone(),
+            food_sources: Vec::new(),
+            next_food_id: 0,
+            next_nest_id: 0,
+            next_ant_id: 0,
+            seed: config.general.seed,
+            rng: SimRng::new(config.general.seed),
+            config,
+            new_round_pending: true,
+            tick: 0,
+            score: Scoreboard::new(),
+            deaths: DeathLog::new(),
+            ledger: EnergyLedger::new(),
+            recalled_nests: Vec::new(),
+            signals: Vec::new(),
+            record_replay: false,
+            replay: None,
+        }
+    }
+}
+
+impl Ground {
+    pub fn size(&self) -> Vector2D {
+        self.size
+    }
+
+    pub fn num_ants(&self) -> usize {
+        self.ants.len()
+    }
+
+    pub fn num_foods(&self) -> usize {
+        self.food.len()
+    }
+
+    pub fn num_colonies(&self) -> usize {
+        let mut colonies: Vec<usize> = self.nests.iter().map(|n| n.colony).collect();
+        colonies.sort_unstable();
+        colonies.dedup();
+        colonies.len()
+    }
+
+    /// Number of updates since the current round started
+    pub fn tick(&self) -> usize {
+        self.tick
+    }
+
+    /// Seed the current round was started with
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Total food unloaded at nests in the current round
+    pub fn food_delivered(&self) -> u64 {
+        self.score.total_food_delivered()
+    }
+
+    /// Metrics of every colony of the current round, including dead ones
+    pub fn score(&self) -> &Scoreboard {
+        &self.score
+    }
+
+    /// Energy flows of the last tick
+    pub fn ledger(&self) -> &EnergyLedger {
+        &self.ledger
+    }
+
+    /// Energy in food on the ground, in ants, carried by ants and in nests
+    pub fn total_energy(&self) -> u64 {
+        let food: u64 = self.food.iter().map(|f| u64::from(f.nutrition)).sum();
+        let ants: u64 = self
+            .ants
+            .iter()
+            .map(|a| u64::from(a.energy) + u64::from(a.carrying))
+            .sum();
+        let nests: u64 = self
+            .nests
+            .iter()
+            .map(|n| u64::from(n.energy) + u64::from(n.granary))
+            .sum();
+        food + ants + nests
+    }
+
+    /// Writes the whole world to a file, so it can be continued later with [`Ground::load`]
+    pub fn save(&self, path: &Path) -> Result<(), PersistError> {
+        persist::save_ron(path, self)
+    }
+
+    /// Reads a world written by [`Ground::save`], refusing configs the simulation cannot run with
+    pub fn load(path: &Path) -> Result<Ground, PersistError> {
+        let mut ground: Ground = persist::load_ron(path)?;
+        ground
+            .config
+            .validate()
+            .map_err(PersistError::InvalidConfig)?;
+        ground.grid = SpatialGrid::new(ground.size, ground.config.ants.max_vision_range());
+        Ok(ground)
+    }
+
+    /// Records the decisions of every tick from the next round on, see [`Ground::replay`]
+    pub fn set_recording(&mut self, enabled: bool) {
+        self.record_replay = enabled;
+        if !enabled {
+            self.replay = None;
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.record_replay
+    }
+
+    /// Decisions recorded so far in the current round
+    pub fn replay(&self) -> Option<&Replay> {
+        self.replay.as_ref()
+    }
+
+    pub fn start_new_round(&mut self) {
+        self.new_round_pending = true;
+    }
+}
+
+impl Ground {
+    fn nest_positions(&self) -> Vec<Vector2D> {
+        self.nests.iter().map(|nest| nest.pos).collect()
+    }
+
+    /// `first` is the fixed point of the first pellet, see [`Surroundings::first`]
+    fn generate_food(&mut self, amount: i32, first: usize) {
+        let nests = self.nest_positions();
+        let around = Surroundings {
+            size: self.size,
+            nests: &nests,
+            patches: &self.food_patches,
+            first,
+            obstacles: &self.obstacles,
+        };
+        let positions = placement::positions(
+            &self.config.food.placement,
+            amount.max(0) as usize,
+            &around,
+            &mut self.rng,
+        );
+
+        for pos in positions {
+            let new_food = FoodPellet::new_at_pos(
+                self.next_food_id,
+                pos,
+                self.config.food.nutrition,
+                self.config.food.eaten_value,
+            );
+            self.ledger
+                .record(EnergyFlow::FoodSpawned, new_food.nutrition);
+            self.food.push(new_food);
+
+            self.next_food_id += 1;
+        }
+    }
+
+    /// The fixed sources of the world and `food.sources` more at random places
+    fn generate_food_sources(&mut self) {
+        self.food_sources = self
+            .config
+            .world
+            .food_sources
+            .iter()
+            .map(FoodSource::new)
+            .collect();
+
+        let food_config = &self.config.food;
+        let placement = &food_config.source_placement;
+        let nests = self.nest_positions();
+        let patches = placement::patch_centers(placement, self.size, &mut self.rng);
+        let around = Surroundings {
+            size: self.size,
+            nests: &nests,
+            patches: &patches,
+            first: 0,
+            obstacles: &self.obstacles,
+        };
+        let positions = placement::positions(
+            placement,
+            food_config.sources as usize,
+            &around,
+            &mut self.rng,
+        );
+
+        self.food_sources
+            .extend(positions.into_iter().map(|position| {
+                FoodSource::new(&FoodSourceConfig {
+                    position,
+                    capacity: food_config.source_capacity,
+                    regrowth: food_config.source_regrowth,
+                })
+            }));
+    }
+
+    fn generate_ants(&mut self, nest: usize, caste: Caste, amount: i32, memory_size: usize) {
+        let Nest {
+            id, colony, pos, ..
+        } = self.nests[nest];
+        for _ in 0..amount {
+            let ant = Ant::new_at_pos(self.next_ant_id, colony, id, &self.config.ants, caste, pos);
+            self.score.record_spawn(colony);
+            self.ledger.record(EnergyFlow::AntBorn, ant.energy);
+            self.ants.push(ant);
+            self.ant_memory.push(MemoryBlock::new(memory_size));
+            self.food_outcomes.push(FoodOutcome::default());
+            self.next_ant_id += 1;
+        }
+    }
+
+    /// Spawns up to `num` ants for the nest at index `nest`, as far as the nest config allows
+    fn spawn_ants(
+        &mut self,
+        nest: usize,
+        num: usize,
+        caste: Caste,
+        decisions: &Decisions,
+    ) -> SpawnDenial {
+        let id = self.nests[nest].id;
+        let memory_size = decisions.memory_size(self.nests[nest].colony);
+        let config = &self.config.nests;
+        let cost = config.spawn_cost + self.config.ants.caste(caste).energy_cost;
+        let needed = cost.saturating_add(config.min_reserve);
+        let max_population = config.max_population as usize;
+        let population = self.ants.iter().filter(|ant| ant.nest == id).count();
+
+        for spawned in 0..num {
+            if max_population != 0 && population + spawned >= max_population {
+                return SpawnDenial::PopulationLimit;
+            }
+            if self.nests[nest].energy <= needed {
+                return SpawnDenial::NotEnoughEnergy;
+            }
+
+            self.generate_ants(nest, caste, 1, memory_size);
+            self.nests[nest].energy -= cost;
+            self.ledger.record(EnergyFlow::SpawnCost, cost);
+        }
+        SpawnDenial::None
+    }
+
+    /// Founds a satellite of the nest at index `nest` at `pos`, handing it `energy`
+    fn found_nest(&mut self, nest: usize, pos: Vector2D, energy: u32) -> SpawnDenial {
+        let config = &self.config.nests;
+        let parent = &self.nests[nest];
+
+        let in_world =
+            (0.0..=self.size.x()).contains(&pos.x()) && (0.0..=self.size.y()).contains(&pos.y());
+        if !in_world
+            || parent.pos.distance(pos) > config.found_range
+            || self.obstacles.iter().any(|o| o.contains(pos))
+        {
+            return SpawnDenial::InvalidSite;
+        }
+
+        // A nest without energy would die right away
+        let energy = energy.min(config.max_energy);
+        let cost = config.found_cost.saturating_add(energy);
+        if energy == 0 || parent.energy <= cost.saturating_add(config.min_reserve) {
+            return SpawnDenial::NotEnoughEnergy;
+        }
+
+        let satellite =
+            Nest::new_satellite(self.next_nest_id, parent, pos, energy, &self.config.nests);
+        self.ledger
+            .record(EnergyFlow::FoundCost, self.config.nests.found_cost);
+        self.nests[nest].energy -= cost;
+        self.nests.push(satellite);
+        self.next_nest_id += 1;
+        SpawnDenial::None
+    }
+
+    fn generate_colonies(&mut self, amount: u32) {
+        let placement = &self.config.nests.placement;
+        let patches = placement::patch_centers(placement, self.size, &mut self.rng);
+        let around = Surroundings {
+            size: self.size,
+            nests: &[],
+            patches: &patches,
+            first: 0,
+            obstacles: &self.obstacles,
+        };
+        let positions = placement::positions(placement, amount as usize, &around, &mut self.rng);
+
+        for pos in positions {
+            let new_colony = Nest::new_at_pos(self.next_nest_id, pos, &self.config.nests);
+
+            self.score.record_colony(new_colony.id);
+            self.nests.push(new_colony);
+
+            self.next_nest_id += 1;
+        }
+    }
+
+    pub fn reset_food_time(&mut self) {
+        self.food_timer = self.config.food.spawn_time;
+    }
+
+    fn push_ant_into_boundary(ant: &mut Ant, size: Vector2D) {
+        if ant.position.x() < 0.0 {
+            let old_pos = ant.position.y();
+            ant.position = Vector2D::new(0.0, old_pos);
+        }
+
+        if ant.position.x() > size.x() {
+            let old_pos = ant.position.y();
+            ant.position = Vector2D::new(size.x(), old_pos);
+        }
+
+        if ant.position.y() < 0.0 {
+            let old_pos = ant.position.x();
+            ant.position = Vector2D::new(old_pos, 0.0);
+        }
+
+        if ant.position.y() > size.y() {
+            let old_pos = ant.position.x();
+            ant.position = Vector2D::new(old_pos, size.y());
+        }
+    }
+
+    /// Generator handed to the plugins for one entity in the current tick.
+    /// Only depends on seed, tick and id, not on the order entities are updated in.
+    fn plugin_rng(seed: u64, tick: usize, stream: u64, id: usize) -> SimRng {
+        SimRng::new(seed)
+            .derive(tick as u64)
+            .derive(stream)
+            .derive(id as u64)
+    }
+
+    fn decide_nests(&self, decisions: &Decisions) -> Result<Vec<NestAction>, ReplayError> {
+        match decisions {
+            Decisions::Plugins(behaviors) => Ok(self
+                .nests
+                .iter()
+                .map(|nest| {
+                    let behavior = ColonyBehavior::for_colony(behaviors, nest.colony);
+                    let score = self.score.get(nest.colony);
+                    // Counts that do not fit are reported as the most there can be
+                    let saturate = |count: u64| u32::try_from(count).unwrap_or(u32::MAX);
+                    let mut context = NestContext {
+                        rng: Ground::plugin_rng(self.seed, self.tick, NEST_RNG_STREAM, nest.id),
+                        tick: self.tick as u64,
+                        living_ants: score.map_or(0, |s| saturate(s.living_ants)),
+                        recent_food: score.map_or(0, |s| saturate(s.recent_food())),
+                        ants_lost: score.map_or(0, |s| saturate(s.ants_lost)),
+                    };
+                    (behavior.nest_func)(nest, &mut context)
+                })
+                .collect()),
+            Decisions::Recorded(record) if record.nests.len() == self.nests.len() => {
+                Ok(record.nests.clone())
+            }
+            Decisions::Recorded(_) => Err(ReplayError::Desync { tick: self.tick }),
+        }
+    }
+
+    fn update_nests(&mut self, _dt: Duration, decisions: &Decisions) -> Result<(), ReplayError> {
+        let actions = self.decide_nests(decisions)?;
+        if let Some(replay) = &mut self.replay {
+            replay.ticks.push(TickRecord {
+                nests: actions.clone(),
+                ants: Vec::new(),
+            });
+        }
+
+        self.recalled_nests.clear();
+        self.signals.clear();
+
+        // Satellites founded in this tick are pushed behind the nests that decided
+        for (i, nest_action) in actions.into_iter().enumerate() {
+            self.nests[i].spawn_denied = SpawnDenial::None;
+            match nest_action {
+                NestAction::Nothing => (),
+                NestAction::SpawnAnts(num, caste) => {
+                    self.nests[i].spawn_denied = self.spawn_ants(i, num, caste, decisions);
+                }
+                NestAction::FoundNest(pos, energy) => {
+                    self.nests[i].spawn_denied = self.found_nest(i, pos, energy);
+                }
+                NestAction::StoreFood(amount) => {
+                    let config = &self.config.nests;
+                    let nest = &mut self.nests[i];
+                    let room = config.granary_capacity.saturating_sub(nest.granary);
+                    // The nest keeps its reserve and enough energy to outlive the next decay
+                    let reserve = config
+                        .min_reserve
+                        .max(config.energy_loss_amount.saturating_add(1));
+                    let stored = amount.min(room).min(nest.energy.saturating_sub(reserve));
+                    nest.energy -= stored;
+                    nest.granary += stored;
+                }
+                NestAction::TakeFood(amount) => {
+                    let nest = &mut self.nests[i];
+                    let room = self.config.nests.max_energy.saturating_sub(nest.energy);
+                    let taken = amount.min(room).min(nest.granary);
+                    nest.granary -= taken;
+                    nest.energy += taken;
+                }
+                NestAction::RecallAnts => self.recalled_nests.push(self.nests[i].id),
+                NestAction::Broadcast(signal) => {
+                    self.signals.push((self.nests[i].colony, signal));
+                }
+            }
+
+            self.nests[i].rounds_to_energy_loss -= 1;
+            if self.nests[i].rounds_to_energy_loss == 0 {
+                let energy = self.nests[i].energy;
+                self.nests[i].energy = energy.saturating_sub(self.config.nests.energy_loss_amount); // Nests have to spend energy to be alive
+                self.ledger
+                    .record(EnergyFlow::NestDecay, energy - self.nests[i].energy);
+
+                self.nests[i].rounds_to_energy_loss = self.config.nests.energy_loss_rounds;
+            }
+
+            if (self.tick + 1).is_multiple_of(self.config.nests.granary_loss_rounds as usize) {
+                let granary = self.nests[i].granary;
+                self.nests[i].granary =
+                    granary.saturating_sub(self.config.nests.granary_loss_amount);
+                self.ledger
+                    .record(EnergyFlow::GranaryDecay, granary - self.nests[i].granary);
+            }
+        }
+        Ok(())
+    }
+
+    /// First half of the ant update: every ant decides what to do, looking at the world as it was
+    /// before any ant acted. The ants are asked in parallel, so plugins have to be thread safe.
+    fn decide_ants(&mut self, behaviors: &[ColonyBehavior]) -> Vec<AntAction> {
+        let max_vision = self.config.ants.max_vision_range();
+
+        if self.grid.cell_size() != max_vision.max(1.0) {
+            self.grid = SpatialGrid::new(self.size, max_vision);
+        }
+        self.grid.rebuild(&self.ants, &self.food);
+
+        let ants = &self.ants;
+        let food = &self.food;
+        let food_outcomes = &self.food_outcomes;
+        let grid = &self.grid;
+        let pheromones = &self.pheromones;
+        let obstacles = &self.obstacles;
+        let config = &self.config;
+        let recalled_nests = &self.recalled_nests;
+        let signals = &self.signals;
+        let (seed, tick) = (self.seed, self.tick);
+
+        self.ant_memory
+            .par_iter_mut()
+            .zip(ants.par_iter())
+            .enumerate()
+            .map_init(Vec::new, |found, (i, (memory, ant))| {
+                // fill ant vision of food and other ants, obstacles are in the way of both
+                let visible =
+                    |pos: Vector2D| !obstacles.iter().any(|o| o.blocks(ant.position, pos));
+                let mut close_by: Vec<Vision> = Vec::new();
+                grid.ants_within(ant.position, ant.vision_range, found);
+                for &j in found
+                    .iter()
+                    .filter(|&&j| j != i && visible(ants[j].position))
+                {
+                    close_by.push(Vision {
+                        object: SeenObject::Ant(ants[j]),
+                        distance: ant.position.distance(ants[j].position),
+                    });
+                }
+
+                grid.food_within(ant.position, ant.vision_range, found);
+                for &j in found.iter().filter(|&&j| visible(food[j].get_position())) {
+                    close_by.push(Vision {
+                        object: SeenObject::Food(food[j]),
+                        distance: ant.position.distance(food[j].get_position()),
+                    });
+                }
+
+                let behavior = ColonyBehavior::for_colony(behaviors, ant.colony);
+                if memory.len() != behavior.memory_size {
+                    // The plugin was reloaded with a different memory layout, the old state is useless
+                    *memory = MemoryBlock::new(behavior.memory_size);
+                }
+                let mut context = AntContext {
+                    vision: FfiSlice::new(&close_by),
+                    pheromones: pheromones.sense(ant, &config.pheromones),
+                    rng: Ground::plugin_rng(seed, tick, ANT_RNG_STREAM, ant.id),
+                    tick: tick as u64,
+                    food_outcome: food_outcomes[i],
+                    memory: memory.view(),
+                    recalled: recalled_nests.contains(&ant.nest),
+                    signal: signals
+                        .iter()
+                        .rev()
+                        .find(|&&(colony, _)| colony == ant.colony)
+                        .map_or(0, |&(_, signal)| signal),
+                };
+                (behavior.ant_func)(ant, &mut context)
+            })
+            .collect()
+    }
+
+    /// Second half of the ant update: applies the decisions one ant after the other in the order
+    /// the ants were spawned, so the outcome does not depend on how the decisions were scheduled
+    fn apply_ant_actions(&mut self, actions: Vec<AntAction>) {
+        let mut food_requests = Vec::new();
+        for outcome in &mut self.food_outcomes {
+            *outcome = FoodOutcome::default();
+        }
+
+        for (i, ant_action) in actions.into_iter().enumerate() {
+            match ant_action {
+                AntAction::Nothing => {}
+                AntAction::GoForward(length) => self.ants[i].go_forward(length, &self.obstacles),
+                AntAction::RotateLeft(angle) => self.ants[i].rotate_left(angle),
+                AntAction::RotateRight(angle) => self.ants[i].rotate_right(angle),
+                // Several ants may want the same pellet, they are served together below
+                AntAction::EatFood(food) => food_requests.push(FoodRequest {
+                    ant: i,
+                    food_id: food.id,
+                    action: FoodAction::Eat,
+                }),
+                AntAction::CarryFood(food) => food_requests.push(FoodRequest {
+                    ant: i,
+                    food_id: food.id,
+                    action: FoodAction::Carry,
+                }),
+                AntAction::UnloadFood => {
+                    // Ants only feed the nests of their own colony
+                    let mut first_closeby_nest = None;
+                    for (idx, nest) in self.nests.iter().enumerate() {
+                        if nest.colony == self.ants[i].colony
+                            && nest.pos.distance(self.ants[i].position)
+                                < self.config.ants.mouth_reach
+                        {
+                            first_closeby_nest = Some(idx);
+
+                            break;
+                        }
+                    }
+
+                    if let Some(nest) = first_closeby_nest {
+                        // Found some nest
+                        // A full nest leaves the rest with the ant
+                        let room = self
+                            .config
+                            .nests
+                            .max_energy
+                            .saturating_sub(self.nests[nest].energy);
+                        let unloaded_food = self.ants[i].carrying.min(room);
+                        self.ants[i].carrying -= unloaded_food;
+                        self.nests[nest].energy += unloaded_food;
+                        self.ledger.record(EnergyFlow::Delivered, unloaded_food);
+                        self.score
+                            .record_delivery(self.nests[nest].colony, unloaded_food);
+                    } else if self.ants[i].carrying > 0 {
+                        // An ant without a load leaves no empty pellet behind
+                        let unloaded_food = self.ants[i].unload_food(&self.config.ants);
+                        self.ledger.record(EnergyFlow::Dropped, unloaded_food);
+
+                        let new_food = FoodPellet::new_at_pos(
+                            self.next_food_id,
+                            self.ants[i].position,
+                            unloaded_food,
+                            self.config.food.eaten_value,
+                        );
+                        self.food.push(new_food);
+
+                        self.next_food_id += 1;
+                    }
+                }
+                AntAction::DepositPheromone(kind, amount) => {
+                    let amount = amount.min(self.config.pheromones.max_deposit);
+                    self.pheromones.deposit(self.ants[i].position, kind, amount);
+                }
+            }
+        }
+
+        feeding::resolve(
+            &food_requests,
+            &mut self.ants,
+            &mut self.food,
+            &mut self.food_outcomes,
+            &mut self.ledger,
+        );
+
+        for i in 0..self.ants.len() {
+            self.ants[i].age += 1;
+
+            self.ants[i].rounds_to_energy_loss -= 1;
+            if self.ants[i].rounds_to_energy_loss == 0 {
+                let energy = self.ants[i].energy;
+                let upkeep = self
+                    .config
+                    .ants
+                    .caste(self.ants[i].caste)
+                    .energy_loss_amount;
+                self.ants[i].energy = energy.saturating_sub(upkeep); // Ants have to spend energy to be alive
+                self.ledger
+                    .record(EnergyFlow::AntDecay, energy - self.ants[i].energy);
+
+                self.ants[i].rounds_to_energy_loss = self.config.ants.energy_loss_rounds;
+            }
+
+            Ground::push_ant_into_boundary(&mut self.ants[i], self.size);
+        }
+    }
+
+    fn update_ants(&mut self, _dt: Duration, decisions: &Decisions) -> Result<(), ReplayError> {
+        let actions = match decisions {
+            Decisions::Plugins(behaviors) => self.decide_ants(behaviors),
+            Decisions::Recorded(record) if record.ants.len() == self.ants.len() => {
+                record.ants.clone()
+            }
+            Decisions::Recorded(_) => return Err(ReplayError::Desync { tick: self.tick }),
+        };
+        if let Some(record) = self.replay.as_mut().and_then(|r| r.ticks.last_mut()) {
+            record.ants = actions.clone();
+        }
+
+        self.apply_ant_actions(actions);
+        Ok(())
+    }
+
+    fn cleanup_ground(&mut self, _dt: Duration) {
+        for ant in &self.ants {
+            if let Some(cause) = DeathCause::of(ant) {
+                self.ledger
+                    .record(EnergyFlow::LostWithAnt, ant.energy + ant.carrying);
+                self.deaths.record(Death {
+                    tick: self.tick,
+                    ant_id: ant.id,
+                    colony: ant.colony,
+                    age: ant.age,
+                    cause,
+                });
+                self.score.record_death(ant.colony);
+            }
+        }
+
+        // Memory and food outcomes are freed together with their ant
+        let alive: Vec<bool> = self.ants.iter().map(|x| x.is_alive()).collect();
+        let mut still_alive = alive.iter();
+        self.ant_memory
+            .retain(|_| *still_alive.next().unwrap_or(&false));
+        let mut still_alive = alive.iter();
+        self.food_outcomes
+            .retain(|_| *still_alive.next().unwrap_or(&false));
+        for nest in self.nests.iter().filter(|x| !x.is_alive()) {
+            self.ledger.record(EnergyFlow::LostWithNest, nest.granary);
+        }
+
+        self.ants.retain(|x| x.is_alive());
+        self.food.retain(|x| x.is_some_left());
+        self.nests.retain(|x| x.is_alive());
+    }
+
+    fn grow_food_sources(&mut self, amount: impl Fn(&FoodSource) -> u32) {
+        for source in &mut self.food_sources {
+            let grown = source.grow(
+                amount(source),
+                &mut self.food,
+                &mut self.next_food_id,
+                self.config.food.eaten_value,
+            );
+            self.ledger.record(EnergyFlow::FoodSpawned, grown);
+        }
+    }
+
+    fn spawn_new_food(&mut self, _dt: Duration) {
+        self.grow_food_sources(|source| source.regrowth);
+
+        if self.config.food.spawn_time <= 0 {
+            return;
+        }
+        self.food_timer -= 1;
+        if self.food_timer == 0 {
+            self.food_timer = self.config.food.spawn_time;
+            // Timed pellets go through the fixed points after the ones of the round start
+            let first = self.config.food.start_amount.max(0) as usize
+                + self.tick / self.config.food.spawn_time as usize;
+            self.generate_food(1, first);
+        }
+    }
+
+    /// Advances the world by one tick. Nest `n` and its ants are controlled by
+    /// `behaviors[n % behaviors.len()]`.
+    pub fn update(&mut self, dt: Duration, behaviors: &[ColonyBehavior]) {
+        if behaviors.is_empty() {
+            return;
+        }
+
+        self.step(dt, &Decisions::Plugins(behaviors))
+            .expect("plugins decide for every nest and ant");
+    }
+
+    /// Advances the world by one tick of a [`Replay`], instead of asking the plugins
+    pub fn replay_tick(&mut self, dt: Duration, record: &TickRecord) -> Result<(), ReplayError> {
+        self.step(dt, &Decisions::Recorded(record))
+    }
+
+    fn step(&mut self, dt: Duration, decisions: &Decisions) -> Result<(), ReplayError> {
+        if self.new_round_pending {
+            self.new_round_pending = false;
+
+            if let Decisions::Plugins(behaviors) = decisions {
+                for behavior in behaviors.iter() {
+                    (behavior.reset_func)();
+                }
+            }
+
+            self.ants.clear();
+            self.ant_memory.clear();
+            self.food_outcomes.clear();
+            self.food.clear();
+            self.nests.clear();
+
+            self.next_food_id = 0;
+            self.next_nest_id = 0;
+            self.next_ant_id = 0;
+
+            self.tick = 0;
+            self.score.clear();
+            self.deaths.clear();
+
+            self.seed = self.config.general.seed;
+            self.rng = SimRng::new(self.seed);
+
+            self.size = self.config.world.size();
+            self.obstacles = self.config.world.obstacles.clone();
+            self.pheromones = PheromoneField::new(self.size, self.config.pheromones.cell_size);
+            self.grid = SpatialGrid::new(self.size, self.config.ants.max_vision_range());
+
+            self.generate_colonies(self.config.nests.colonies.max(1));
+            self.food_patches =
+                placement::patch_centers(&self.config.food.placement, self.size, &mut self.rng);
+            self.generate_food(self.config.food.start_amount, 0);
+            self.generate_food_sources();
+            // Sources start full
+            self.grow_food_sources(|source| source.capacity);
+
+            self.replay = self.record_replay.then(|| Replay::new(self.config.clone()));
+        }
+
+        self.ledger.clear();
+        let check_energy = self.config.general.check_energy;
+        let energy_before = if check_energy { self.total_energy() } else { 0 };
+
+        self.update_nests(dt, decisions)?;
+        self.update_ants(dt, decisions)?;
+        self.pheromones.update(&self.config.pheromones);
+
+        self.cleanup_ground(dt);
+        self.score.record_tick(&self.nests, &self.ants);
+
+        self.spawn_new_food(dt);
+
+        if check_energy {
+            let change = self.total_energy() as i64 - energy_before as i64;
+            assert_eq!(
+                change,
+                self.ledger.net_change(),
+                "energy not conserved in tick {}: {:?}",
+                self.tick,
+                self.ledger
+            );
+        }
+
+        self.tick += 1;
+        Ok(())
+    }
+
+    pub fn ant_list(&self) -> &Vec<Ant> {
+        &self.ants
+    }
+
+    pub fn food_list(&self) -> &Vec<FoodPellet> {
+        &self.food
+    }
+
+    pub fn nest_list(&self) -> &Vec<Nest> {
+        &self.nests
+    }
+
+    pub fn pheromones(&self) -> &PheromoneField {
+        &self.pheromones
+    }
+
+    pub fn deaths(&self) -> &DeathLog {
+        &self.deaths
+    }
+
+    pub fn obstacles(&self) -> &[Obstacle] {
+        &self.obstacles
+    }
+
+    pub fn food_sources(&self) -> &[FoodSource] {
+        &self.food_sources
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::pheromones::PheromoneKind;
+    use config::ConfigError;
+    use std::sync::Mutex;
+
+    const DT: Duration = Duration::from_millis(16);
+
+    extern "C" fn no_reset() {}
+
+    fn behavior(ant_func: crate::AntFn, nest_func: crate::NestFn) -> ColonyBehavior {
+        ColonyBehavior {
+            ant_func,
+            nest_func,
+            reset_func: no_reset,
+            memory_size: 0,
+        }
+    }
+
+    /// Nests and food on fixed points, without random pellets
+    fn fixed_config(nests: &[(f32, f32)], food: &[(f32, f32)]) -> Config {
+        let points = |points: &[(f32, f32)]| {
+            points
+                .iter()
+                .map(|&(x, y)| Vector2D::new(x, y))
+                .collect::<Vec<_>>()
+        };
+        let mut config = Config::new();
+        config.general.check_energy = true;
+        config.nests.colonies = nests.len() as u32;
+        config.nests.placement = config::Placement::Fixed {
+            points: points(nests),
+        };
+        config.food.placement = config::Placement::Fixed {
+            points: points(food),
+        };
+        config.food.start_amount = food.len() as i32;
+        config.food.spawn_time = 0;
+        config
+    }
+
+    static CARRY_OUTCOMES: Mutex<Vec<(u64, usize, FoodOutcome)>> = Mutex::new(Vec::new());
+
+    extern "C" fn spawn_two(_nest: &Nest, context: &mut NestContext) -> NestAction {
+        match context.tick {
+            0 => NestAction::SpawnAnts(2, Caste::Worker),
+            _ => NestAction::Nothing,
+        }
+    }
+
+    extern "C" fn carry_first_seen(ant: &Ant, context: &mut AntContext) -> AntAction {
+        CARRY_OUTCOMES
+            .lock()
+            .unwrap()
+            .push((context.tick, ant.id, context.food_outcome));
+        context
+            .vision
+            .iter()
+            .find_map(|seen| match seen.object {
+                SeenObject::Food(food) => Some(AntAction::CarryFood(food)),
+                SeenObject::Ant(_) => None,
+            })
+            .unwrap_or(AntAction::Nothing)
+    }
+
+    #[test]
+    fn ants_get_their_food_outcome_in_the_next_tick() {
+        let mut config = fixed_config(&[(500.0, 500.0)], &[(500.0, 500.0)]);
+        config.food.nutrition = 30;
+        let mut ground = Ground::new(config);
+        let behaviors = [behavior(carry_first_seen, spawn_two)];
+
+        ground.update(DT, &behaviors);
+        assert_eq!(ground.ledger().get(EnergyFlow::PickedUp), 30);
+        assert_eq!(ground.num_foods(), 0);
+        ground.update(DT, &behaviors);
+
+        let mut seen = CARRY_OUTCOMES.lock().unwrap().clone();
+        seen.sort_by_key(|&(tick, ant, _)| (tick, ant));
+        let outcomes: Vec<(u64, usize, FoodAction, u32, u32)> = seen
+            .iter()
+            .map(|&(tick, ant, o)| (tick, ant, o.action, o.requested, o.received))
+            .collect();
+        assert_eq!(
+            outcomes,
+            [
+                (0, 0, FoodAction::None, 0, 0),
+                (0, 1, FoodAction::None, 0, 0),
+                (1, 0, FoodAction::Carry, 30, 30),
+                (1, 1, FoodAction::Carry, 30, 0),
+            ]
+        );
+    }
+
+    /// Takes a random action, eating and carrying only food it sees
+    extern "C" fn wander(ant: &Ant, context: &mut AntContext) -> AntAction {
+        let food = context.vision.iter().find_map(|seen| match seen.object {
+            SeenObject::Food(food) => Some(food),
+            SeenObject::Ant(_) => None,
+        });
+        let rng = &mut context.rng;
+        match (rng.gen_range(0, 8), food) {
+            (0, _) => AntAction::RotateLeft(rng.gen_f32()),
+            (1, _) => AntAction::RotateRight(rng.gen_f32()),
+            (2, Some(food)) => AntAction::EatFood(food),
+            (3, Some(food)) => AntAction::CarryFood(food),
+            (4, _) => AntAction::UnloadFood,
+            (5, _) => AntAction::DepositPheromone(PheromoneKind::Food, rng.gen_f32()),
+            _ => AntAction::GoForward(ant.speed),
+        }
+    }
+
+    /// Takes a random action, founding satellites around itself
+    extern "C" fn busy_nest(nest: &Nest, context: &mut NestContext) -> NestAction {
+        let rng = &mut context.rng;
+        match rng.gen_range(0, 10) {
+            0 | 1 => NestAction::SpawnAnts(
+                rng.gen_range(1, 3) as usize,
+                Caste::ALL[rng.gen_range(0, 4) as usize],
+            ),
+            2 => {
+                let offset = Vector2D::new(rng.gen_f32() - 0.5, rng.gen_f32() - 0.5) * 500.0;
+                NestAction::FoundNest(nest.pos + offset, rng.gen_range(1, 2000))
+            }
+            3 => NestAction::StoreFood(rng.gen_range(0, 3000)),
+            4 => NestAction::TakeFood(rng.gen_range(0, 3000)),
+            5 => NestAction::RecallAnts,
+            6 => NestAction::Broadcast(rng.gen_range(1, 100)),
+            _ => NestAction::Nothing,
+        }
+    }
+
+    /// Short lived ants and food close to the nests, so ants are born, eat, carry and die
+    fn busy_config(seed: u64, colonies: u32) -> Config {
+        let mut config = Config::new();
+        config.general.seed = seed;
+        config.general.check_energy = true;
+        config.nests.colonies = colonies;
+        config.ants.lifespan = 300;
+        config.food.placement = config::Placement::Ring { radius: 40.0 };
+        config.food.start_amount = 20;
+        config.food.spawn_time = 10;
+        config.food.nutrition = 200;
+        config.food.eaten_value = 50;
+        config
+    }
+
+    /// Runs `ticks` updates and checks after each that the world total changed by exactly what
+    /// the ledger says. Returns the ground and the sum of every flow over all ticks.
+    fn run_conserving(
+        config: Config,
+        behaviors: &[ColonyBehavior],
+        ticks: usize,
+    ) -> (Ground, [u64; EnergyFlow::COUNT]) {
+        let mut ground = Ground::new(config);
+        let mut totals = [0; EnergyFlow::COUNT];
+        for _ in 0..ticks {
+            // The first update creates the world before the tick starts
+            if ground.tick() == 0 {
+                ground.update(DT, behaviors);
+            } else {
+                let before = ground.total_energy() as i64;
+                ground.update(DT, behaviors);
+                let change = ground.total_energy() as i64 - before;
+                assert_eq!(
+                    change,
+                    ground.ledger().net_change(),
+                    "tick {}",
+                    ground.tick()
+                );
+            }
+            for flow in EnergyFlow::ALL {
+                totals[flow.index()] += ground.ledger().get(flow);
+            }
+        }
+        (ground, totals)
+    }
+
+    #[test]
+    fn one_colony_conserves_energy() {
+        let behaviors = [behavior(wander, busy_nest)];
+        let (_, totals) = run_conserving(busy_config(3, 1), &behaviors, 1000);
+
+        for flow in [
+            EnergyFlow::AntBorn,
+            EnergyFlow::SpawnCost,
+            EnergyFlow::Eaten,
+            EnergyFlow::PickedUp,
+            EnergyFlow::Dropped,
+            EnergyFlow::LostWithAnt,
+        ] {
+            assert!(totals[flow.index()] > 0, "no {:?}", flow);
+        }
+    }
+
+    #[test]
+    fn several_colonies_conserve_energy() {
+        let behaviors = [behavior(wander, busy_nest), behavior(wander, spawn_two)];
+        let (ground, totals) = run_conserving(busy_config(11, 4), &behaviors, 800);
+
+        assert_eq!(ground.score().colonies().len(), 4);
+        assert!(totals[EnergyFlow::Eaten.index()] > 0);
+    }
+
+    #[test]
+    fn food_sources_conserve_energy() {
+        let mut config = busy_config(5, 1);
+        config.food.start_amount = 0;
+        config.food.spawn_time = 0;
+        config.food.sources = 3;
+        config.food.source_capacity = 400;
+        config.food.source_regrowth = 3;
+        config.food.source_placement = config::Placement::Ring { radius: 30.0 };
+        let behaviors = [behavior(wander, busy_nest)];
+        let (ground, totals) = run_conserving(config, &behaviors, 1000);
+
+        assert_eq!(ground.food_sources().len(), 3);
+        // Only the sources grow food, and it is eaten
+        assert!(totals[EnergyFlow::FoodSpawned.index()] > 0);
+        assert!(totals[EnergyFlow::Eaten.index()] + totals[EnergyFlow::PickedUp.index()] > 0);
+    }
+
+    #[test]
+    fn granaries_and_satellites_conserve_energy() {
+        let mut config = busy_config(8, 2);
+        config.nests.start_energy = 9000;
+        config.nests.found_cost = 500;
+        config.nests.granary_loss_rounds = 7;
+        let behaviors = [behavior(wander, busy_nest)];
+        let (_, totals) = run_conserving(config, &behaviors, 1000);
+
+        for flow in [
+            EnergyFlow::FoundCost,
+            EnergyFlow::GranaryDecay,
+            EnergyFlow::NestDecay,
+        ] {
+            assert!(totals[flow.index()] > 0, "no {:?}", flow);
+        }
+    }
+
+    #[test]
+    fn snapshots_with_an_invalid_config_are_refused() {
+        let path = std::env::temp_dir().join(format!("ant_sim_invalid_{}.ron", std::process::id()));
+        let mut ground = Ground::new(busy_config(1, 1));
+        ground.update(DT, &[behavior(wander, busy_nest)]);
+        ground.save(&path).unwrap();
+        assert!(Ground::load(&path).is_ok());
+
+        // Like a hand-edited snapshot, the countdowns of nests and ants would underflow
+        ground.config.nests.energy_loss_rounds = 0;
+        ground.save(&path).unwrap();
+        let loaded = Ground::load(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(
+            loaded,
+            Err(PersistError::InvalidConfig(ConfigError::NotPositive(
+                "nests.energy_loss_rounds"
+            )))
+        ));
+    }
+
+    extern "C" fn keep_thirty(_nest: &Nest, context: &mut NestContext) -> NestAction {
+        match context.living_ants {
+            0..30 => NestAction::SpawnAnts(1, Caste::Worker),
+            _ => NestAction::Nothing,
+        }
+    }
+
+    /// Mostly walks straight ahead, so the ants run into the obstacles around the nest
+    extern "C" fn run_around(ant: &Ant, context: &mut AntContext) -> AntAction {
+        let rng = &mut context.rng;
+        match rng.gen_range(0, 10) {
+            0 => AntAction::RotateLeft(rng.gen_f32()),
+            1 => AntAction::RotateRight(rng.gen_f32()),
+            _ => AntAction::GoForward(ant.speed),
+        }
+    }
+
+    #[test]
+    fn ants_never_end_a_tick_inside_an_obstacle() {
+        let mut config = fixed_config(&[(500.0, 500.0)], &[]);
+        config.ants.worker.speed = 3.0;
+        config.nests.spawn_cost = 10;
+        config.world.obstacles = vec![
+            Obstacle::Rect {
+                min: Vector2D::new(530.0, 420.0),
+                max: Vector2D::new(545.0, 580.0),
+            },
+            Obstacle::Circle {
+                center: Vector2D::new(500.0, 450.0),
+                radius: 25.0,
+            },
+            // A U around the nest from below, its arms reach up on both sides
+            Obstacle::Polygon {
+                points: vec![
+                    Vector2D::new(440.0, 560.0),
+                    Vector2D::new(520.0, 560.0),
+                    Vector2D::new(520.0, 530.0),
+                    Vector2D::new(510.0, 530.0),
+                    Vector2D::new(510.0, 550.0),
+                    Vector2D::new(450.0, 550.0),
+                    Vector2D::new(450.0, 480.0),
+                    Vector2D::new(440.0, 480.0),
+                ],
+            },
+        ];
+        let obstacles = config.world.obstacles.clone();
+        let mut ground = Ground::new(config);
+        let behaviors = [behavior(run_around, keep_thirty)];
+
+        for _ in 0..600 {
+            ground.update(DT, &behaviors);
+            for ant in ground.ant_list() {
+                assert!(
+                    !obstacles.iter().any(|o| o.contains(ant.position)),
+                    "ant {} at {:?} in tick {}",
+                    ant.id,
+                    ant.position,
+                    ground.tick()
+                );
+            }
+        }
+        assert_eq!(ground.num_ants(), 30);
+    }
+
+    static SEEN_FOOD: Mutex<Vec<usize>> = Mutex::new(Vec::new());
+
+    extern "C" fn look(_ant: &Ant, context: &mut AntContext) -> AntAction {
+        let mut seen = SEEN_FOOD.lock().unwrap();
+        for vision in &context.vision {
+            if let SeenObject::Food(food) = vision.object {
+                seen.push(food.id);
+            }
+        }
+        AntAction::Nothing
+    }
+
+    #[test]
+    fn ants_do_not_see_behind_walls() {
+        // Pellet 0 is behind the wall, pellet 1 just as close in the open
+        let mut config = fixed_config(&[(200.0, 500.0)], &[(290.0, 500.0), (200.0, 590.0)]);
+        config.world.obstacles = vec![Obstacle::Rect {
+            min: Vector2D::new(240.0, 300.0),
+            max: Vector2D::new(250.0, 700.0),
+        }];
+        let mut ground = Ground::new(config);
+        let behaviors = [behavior(look, spawn_two)];
+
+        ground.update(DT, &behaviors);
+        ground.update(DT, &behaviors);
+
+        let seen = SEEN_FOOD.lock().unwrap();
+        assert!(!seen.is_empty());
+        assert!(seen.iter().all(|&id| id == 1), "{:?}", seen);
+    }
+}
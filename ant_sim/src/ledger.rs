@@ -0,0 +1,144 @@
+/// Where energy comes from or goes to.
+///
+/// Energy lives in food pellets, in ants (their energy and the food they carry) and in nests (their energy and granary).
+/// Sources and sinks change the total of the world, transfers only move energy around.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnergyFlow {
+    /// New food pellets appearing on the ground
+    FoodSpawned,
+    /// Energy a new ant starts with
+    AntBorn,
+    /// Nest energy paid for a new ant
+    SpawnCost,
+    /// Nest energy paid for founding a satellite nest
+    FoundCost,
+    AntDecay,
+    NestDecay,
+    GranaryDecay,
+    /// Energy and food an ant had when it died
+    LostWithAnt,
+    /// What was left in the granary of a nest when it died
+    LostWithNest,
+    /// From a pellet into an ant
+    Eaten,
+    /// From a pellet into the load of an ant
+    PickedUp,
+    /// From the load of an ant into its nest
+    Delivered,
+    /// From the load of an ant into a new pellet on the ground
+    Dropped,
+}
+
+impl EnergyFlow {
+    pub const COUNT: usize = 13;
+    pub const ALL: [EnergyFlow; EnergyFlow::COUNT] = [
+        EnergyFlow::FoodSpawned,
+        EnergyFlow::AntBorn,
+        EnergyFlow::SpawnCost,
+        EnergyFlow::FoundCost,
+        EnergyFlow::AntDecay,
+        EnergyFlow::NestDecay,
+        EnergyFlow::GranaryDecay,
+        EnergyFlow::LostWithAnt,
+        EnergyFlow::LostWithNest,
+        EnergyFlow::Eaten,
+        EnergyFlow::PickedUp,
+        EnergyFlow::Delivered,
+        EnergyFlow::Dropped,
+    ];
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    /// 1 for sources, -1 for sinks and 0 for transfers
+    pub fn sign(self) -> i64 {
+        match self {
+            EnergyFlow::FoodSpawned | EnergyFlow::AntBorn => 1,
+            EnergyFlow::SpawnCost
+            | EnergyFlow::FoundCost
+            | EnergyFlow::AntDecay
+            | EnergyFlow::NestDecay
+            | EnergyFlow::GranaryDecay
+            | EnergyFlow::LostWithAnt
+            | EnergyFlow::LostWithNest => -1,
+            EnergyFlow::Eaten
+            | EnergyFlow::PickedUp
+            | EnergyFlow::Delivered
+            | EnergyFlow::Dropped => 0,
+        }
+    }
+}
+
+/// Sum of every kind of energy flow in one tick
+#[derive(Debug, Clone, Default)]
+pub struct EnergyLedger {
+    amounts: [u64; EnergyFlow::COUNT],
+}
+
+impl EnergyLedger {
+    pub fn new() -> EnergyLedger {
+        EnergyLedger::default()
+    }
+
+    pub fn clear(&mut self) {
+        self.amounts = [0; EnergyFlow::COUNT];
+    }
+
+    pub fn record(&mut self, flow: EnergyFlow, amount: u32) {
+        self.amounts[flow.index()] += u64::from(amount);
+    }
+
+    pub fn get(&self, flow: EnergyFlow) -> u64 {
+        self.amounts[flow.index()]
+    }
+
+    /// How much the world total should have changed, sources minus sinks
+    pub fn net_change(&self) -> i64 {
+        EnergyFlow::ALL
+            .iter()
+            .map(|&flow| flow.sign() * self.get(flow) as i64)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn net_change_is_sources_minus_sinks() {
+        let mut ledger = EnergyLedger::new();
+        ledger.record(EnergyFlow::FoodSpawned, 1000);
+        ledger.record(EnergyFlow::AntBorn, 300);
+        ledger.record(EnergyFlow::SpawnCost, 500);
+        ledger.record(EnergyFlow::AntDecay, 20);
+        ledger.record(EnergyFlow::LostWithAnt, 70);
+        // Transfers only move energy around
+        ledger.record(EnergyFlow::Eaten, 400);
+        ledger.record(EnergyFlow::PickedUp, 200);
+        ledger.record(EnergyFlow::Delivered, 150);
+        ledger.record(EnergyFlow::Dropped, 50);
+
+        assert_eq!(ledger.net_change(), 1300 - 590);
+
+        ledger.clear();
+        assert_eq!(ledger.net_change(), 0);
+    }
+
+    #[test]
+    fn every_flow_is_counted() {
+        let mut ledger = EnergyLedger::new();
+        for flow in EnergyFlow::ALL {
+            ledger.record(flow, 1);
+        }
+        let sources = EnergyFlow::ALL.iter().filter(|f| f.sign() > 0).count() as i64;
+        let sinks = EnergyFlow::ALL.iter().filter(|f| f.sign() < 0).count() as i64;
+
+        assert!(EnergyFlow::ALL
+            .iter()
+            .enumerate()
+            .all(|(i, flow)| flow.index() == i && ledger.get(*flow) == 1));
+        assert_eq!(ledger.net_change(), sources - sinks);
+    }
+}
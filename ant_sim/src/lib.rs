@@ -0,0 +1,41 @@
+use common::animals::ant::{Ant, AntAction};
+use common::animals::context::AntContext;
+use common::buildings::context::NestContext;
+use common::buildings::{Nest, NestAction};
+
+pub mod deaths;
+pub mod feeding;
+pub mod ground;
+pub mod ledger;
+pub mod memory;
+pub mod persist;
+pub mod pheromones;
+pub mod placement;
+pub mod replay;
+pub mod scenario;
+pub mod score;
+pub mod sources;
+pub mod spatial;
+
+/// Called for many ants at the same time from several threads, it must not touch shared state
+pub type AntFn = extern "C" fn(&Ant, &mut AntContext) -> AntAction;
+pub type NestFn = extern "C" fn(&Nest, &mut NestContext) -> NestAction;
+pub type ResetFn = extern "C" fn();
+pub type MemorySizeFn = extern "C" fn() -> usize;
+
+/// The plugin functions controlling one colony
+#[derive(Clone, Copy)]
+pub struct ColonyBehavior {
+    pub ant_func: AntFn,
+    pub nest_func: NestFn,
+    pub reset_func: ResetFn,
+    /// Bytes of memory the host keeps for every ant of the colony
+    pub memory_size: usize,
+}
+
+impl ColonyBehavior {
+    /// Picks the behavior of a colony. If there are less behaviors than colonies they are reused round-robin.
+    pub fn for_colony(behaviors: &[ColonyBehavior], colony: usize) -> &ColonyBehavior {
+        &behaviors[colony % behaviors.len()]
+    }
+}
@@ -0,0 +1,32 @@
+use common::animals::memory::AntMemory;
+use serde::{Deserialize, Serialize};
+
+/// Host side storage of one ant's plugin memory
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct MemoryBlock {
+    // u64 words keep the block aligned to AntMemory::ALIGN
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl MemoryBlock {
+    pub fn new(len: usize) -> MemoryBlock {
+        MemoryBlock {
+            words: vec![0; len.div_ceil(AntMemory::ALIGN)],
+            len,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Hands the block to a plugin. The view must not outlive the borrow of `self`.
+    pub fn view(&mut self) -> AntMemory {
+        unsafe { AntMemory::from_raw(self.words.as_mut_ptr() as *mut u8, self.len) }
+    }
+}
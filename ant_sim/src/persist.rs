@@ -0,0 +1,108 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use common::helper::config::{Config, ConfigError};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Reasons why a file could not be written or read
+#[derive(Debug)]
+pub enum PersistError {
+    Io(io::Error),
+    Serialize(ron::Error),
+    Parse(ron::error::SpannedError),
+    Binary(postcard::Error),
+    TomlSerialize(toml::ser::Error),
+    TomlParse(toml::de::Error),
+    InvalidConfig(ConfigError),
+}
+
+impl fmt::Display for PersistError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PersistError::Io(e) => write!(f, "{}", e),
+            PersistError::Serialize(e) => write!(f, "could not serialize: {}", e),
+            PersistError::Parse(e) => write!(f, "invalid file: {}", e),
+            PersistError::Binary(e) => write!(f, "invalid binary data: {}", e),
+            PersistError::TomlSerialize(e) => write!(f, "could not serialize: {}", e),
+            PersistError::TomlParse(e) => write!(f, "invalid file: {}", e),
+            PersistError::InvalidConfig(e) => write!(f, "invalid config: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for PersistError {}
+
+impl From<io::Error> for PersistError {
+    fn from(e: io::Error) -> Self {
+        PersistError::Io(e)
+    }
+}
+
+/// Writes `value` as RON to `path`
+pub fn save_ron<T: Serialize>(path: &Path, value: &T) -> Result<(), PersistError> {
+    let text = ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())
+        .map_err(PersistError::Serialize)?;
+    fs::write(path, text)?;
+    Ok(())
+}
+
+/// Reads a value written by [`save_ron`]
+pub fn load_ron<T: DeserializeOwned>(path: &Path) -> Result<T, PersistError> {
+    let text = fs::read_to_string(path)?;
+    ron::from_str(&text).map_err(PersistError::Parse)
+}
+
+/// Writes `value` in a compact binary format to `path`, for data nobody reads by hand
+pub fn save_binary<T: Serialize>(path: &Path, value: &T) -> Result<(), PersistError> {
+    let bytes = postcard::to_stdvec(value).map_err(PersistError::Binary)?;
+    fs::write(path, bytes)?;
+    Ok(())
+}
+
+/// Reads a value written by [`save_binary`]
+pub fn load_binary<T: DeserializeOwned>(path: &Path) -> Result<T, PersistError> {
+    let bytes = fs::read(path)?;
+    postcard::from_bytes(&bytes).map_err(PersistError::Binary)
+}
+
+/// Writes `value` as TOML to `path`
+pub fn save_toml<T: Serialize>(path: &Path, value: &T) -> Result<(), PersistError> {
+    let text = toml::to_string_pretty(value).map_err(PersistError::TomlSerialize)?;
+    fs::write(path, text)?;
+    Ok(())
+}
+
+/// Reads a value written by [`save_toml`]
+pub fn load_toml<T: DeserializeOwned>(path: &Path) -> Result<T, PersistError> {
+    let text = fs::read_to_string(path)?;
+    toml::from_str(&text).map_err(PersistError::TomlParse)
+}
+
+fn is_toml(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "toml")
+}
+
+/// Writes a config as TOML if `path` ends in `.toml` and as RON otherwise
+pub fn save_config(path: &Path, config: &Config) -> Result<(), PersistError> {
+    if is_toml(path) {
+        save_toml(path, config)
+    } else {
+        save_ron(path, config)
+    }
+}
+
+/// Reads and validates a config written by [`save_config`] or by hand.
+///
+/// Values missing from the file keep their defaults.
+pub fn load_config(path: &Path) -> Result<Config, PersistError> {
+    let config: Config = if is_toml(path) {
+        load_toml(path)?
+    } else {
+        load_ron(path)?
+    };
+    config.validate().map_err(PersistError::InvalidConfig)?;
+    Ok(config)
+}
@@ -0,0 +1,122 @@
+use common::animals::ant::Ant;
+use common::helper::config::PheromoneConfig;
+use common::helper::{Rotation, Vector2D};
+use common::pheromones::{PheromoneKind, PheromoneSample, PheromoneSense};
+use serde::{Deserialize, Serialize};
+
+/// Grid of pheromone concentrations covering the whole ground, one layer per kind
+#[derive(Serialize, Deserialize)]
+pub struct PheromoneField {
+    width: usize,
+    height: usize,
+    cell_size: f32,
+    layers: Vec<Vec<f32>>,
+
+    // technical
+    #[serde(skip)]
+    scratch: Vec<f32>,
+}
+
+impl PheromoneField {
+    pub fn new(size: Vector2D, cell_size: f32) -> PheromoneField {
+        let cell_size = cell_size.max(1.0);
+        let width = (size.x() / cell_size).ceil().max(1.0) as usize;
+        let height = (size.y() / cell_size).ceil().max(1.0) as usize;
+
+        PheromoneField {
+            width,
+            height,
+            cell_size,
+            layers: vec![vec![0.0; width * height]; PheromoneKind::COUNT],
+            scratch: vec![0.0; width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    /// Concentrations of one kind, row by row
+    pub fn layer(&self, kind: PheromoneKind) -> &[f32] {
+        &self.layers[kind.index()]
+    }
+
+    fn cell_index(&self, pos: Vector2D) -> usize {
+        let x = ((pos.x() / self.cell_size).max(0.0) as usize).min(self.width - 1);
+        let y = ((pos.y() / self.cell_size).max(0.0) as usize).min(self.height - 1);
+        y * self.width + x
+    }
+
+    pub fn deposit(&mut self, pos: Vector2D, kind: PheromoneKind, amount: f32) {
+        let idx = self.cell_index(pos);
+        self.layers[kind.index()][idx] += amount.max(0.0);
+    }
+
+    pub fn sample(&self, pos: Vector2D, kind: PheromoneKind) -> f32 {
+        self.layers[kind.index()][self.cell_index(pos)]
+    }
+
+    /// Samples all kinds left, straight and right in front of the ant
+    pub fn sense(&self, ant: &Ant, config: &PheromoneConfig) -> PheromoneSense {
+        let probe = |angle: f32| {
+            let rotation = Rotation::new_rad(ant.rotation.get_rad() + angle);
+            ant.position
+                + Vector2D::new(rotation.get_rad().cos(), -rotation.get_rad().sin())
+                    * config.sense_distance
+        };
+        let left = probe(-config.sense_angle);
+        let center = probe(0.0);
+        let right = probe(config.sense_angle);
+
+        let mut sense = PheromoneSense::default();
+        for kind in PheromoneKind::ALL {
+            sense.samples[kind.index()] = PheromoneSample {
+                left: self.sample(left, kind),
+                center: self.sample(center, kind),
+                right: self.sample(right, kind),
+            };
+        }
+        sense
+    }
+
+    /// Spreads every layer into the four neighbour cells and lets it evaporate
+    pub fn update(&mut self, config: &PheromoneConfig) {
+        let diffusion = config.diffusion.clamp(0.0, 1.0);
+        let keep = 1.0 - config.evaporation.clamp(0.0, 1.0);
+        let (w, h) = (self.width, self.height);
+        // Not saved in snapshots
+        self.scratch.resize(w * h, 0.0);
+
+        for layer in &mut self.layers {
+            for y in 0..h {
+                for x in 0..w {
+                    let idx = y * w + x;
+                    let own = layer[idx];
+                    // Cells at the border reflect, so nothing diffuses out of the world
+                    let left = if x > 0 { layer[idx - 1] } else { own };
+                    let right = if x + 1 < w { layer[idx + 1] } else { own };
+                    let up = if y > 0 { layer[idx - w] } else { own };
+                    let down = if y + 1 < h { layer[idx + w] } else { own };
+
+                    let neighbours = 0.25 * (left + right + up + down);
+                    self.scratch[idx] = ((1.0 - diffusion) * own + diffusion * neighbours) * keep;
+                }
+            }
+            std::mem::swap(layer, &mut self.scratch);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        for layer in &mut self.layers {
+            layer.iter_mut().for_each(|c| *c = 0.0);
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use std::f32::consts::TAU;
+
+use common::helper::config::Placement;
+use common::helper::{Rotation, SimRng, Vector2D};
+use common::terrain::Obstacle;
+
+/// What a [`Placement`] arranges new positions around
+pub struct Surroundings<'a> {
+    pub size: Vector2D,
+    /// Positions of the nests when placing food, empty when placing the nests themselves
+    pub nests: &'a [Vector2D],
+    /// Centers of a clustered placement, see [`patch_centers`]
+    pub patches: &'a [Vector2D],
+    /// Index of the fixed point the first new thing is put on, the others take the following ones
+    pub first: usize,
+    /// New things are not placed inside obstacles
+    pub obstacles: &'a [Obstacle],
+}
+
+/// The patches of a clustered placement, drawn once per round. Empty for other placements.
+pub fn patch_centers(placement: &Placement, size: Vector2D, rng: &mut SimRng) -> Vec<Vector2D> {
+    match *placement {
+        Placement::Clustered { patches, .. } => (0..patches).map(|_| uniform(size, rng)).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// A placement draws again if a position ends up inside an obstacle, but gives up after this many tries
+const MAX_TRIES: usize = 16;
+
+/// Positions for `amount` new things, all on the ground and if possible outside of the obstacles.
+///
+/// Mirrored placements round `amount` up to a multiple of the number of nests, so every colony
+/// gets the same. When the nests themselves are placed, all `amount` of them are one rotated set.
+pub fn positions(
+    placement: &Placement,
+    amount: usize,
+    around: &Surroundings,
+    rng: &mut SimRng,
+) -> Vec<Vector2D> {
+    // Positions are drawn in groups which are redrawn together, so mirrored sets stay symmetric
+    let (group_size, groups) = match placement {
+        Placement::Mirrored if around.nests.is_empty() => (amount, 1),
+        Placement::Mirrored => (around.nests.len(), amount.div_ceil(around.nests.len())),
+        _ => (1, amount),
+    };
+
+    let mut found = Vec::with_capacity(group_size * groups);
+    for group in 0..groups {
+        let index = around.first + group;
+        let mut positions = draw(placement, group_size, index, around, rng);
+        for _ in 1..MAX_TRIES {
+            let free = positions
+                .iter()
+                .all(|&pos| !around.obstacles.iter().any(|o| o.contains(pos)));
+            if free {
+                break;
+            }
+            positions = draw(placement, group_size, index, around, rng);
+        }
+        found.extend(positions);
+    }
+    found
+}
+
+/// One group of positions, see [`positions`]
+fn draw(
+    placement: &Placement,
+    group_size: usize,
+    index: usize,
+    around: &Surroundings,
+    rng: &mut SimRng,
+) -> Vec<Vector2D> {
+    let center = around.size * 0.5;
+
+    let group = match placement {
+        Placement::Uniform => vec![uniform(around.size, rng)],
+        Placement::Clustered { radius, .. } => match pick(around.patches, rng) {
+            Some(patch) => vec![patch + in_disc(*radius, rng)],
+            None => vec![uniform(around.size, rng)],
+        },
+        // Config validation makes sure there are enough points for the start of a round, only
+        // pellets spawned later on wrap around
+        Placement::Fixed { points } => match points.get(index % points.len().max(1)) {
+            Some(&point) => vec![point],
+            None => vec![uniform(around.size, rng)],
+        },
+        Placement::Ring { radius } => {
+            let origin = pick(around.nests, rng).unwrap_or(center);
+            vec![origin + Vector2D::new(*radius, 0.0) * random_rotation(rng)]
+        }
+        Placement::Mirrored => {
+            // Within the largest circle around the center, so the rotated copies stay on the ground
+            let max_radius = 0.5 * around.size.x().min(around.size.y());
+            let offset = in_disc(max_radius, rng);
+            (0..group_size)
+                .map(|k| {
+                    let turn = Rotation::new_rad(TAU * k as f32 / group_size as f32);
+                    center + offset * turn
+                })
+                .collect()
+        }
+    };
+
+    group
+        .into_iter()
+        .map(|pos| clamp_to_ground(pos, around.size))
+        .collect()
+}
+
+fn uniform(size: Vector2D, rng: &mut SimRng) -> Vector2D {
+    let x = rng.gen_f32() * size.x();
+    let y = rng.gen_f32() * size.y();
+    Vector2D::new(x, y)
+}
+
+fn pick(points: &[Vector2D], rng: &mut SimRng) -> Option<Vector2D> {
+    if points.is_empty() {
+        return None;
+    }
+    Some(points[rng.gen_range(0, points.len() as u32) as usize])
+}
+
+fn random_rotation(rng: &mut SimRng) -> Rotation {
+    Rotation::new_rad(rng.gen_f32() * TAU)
+}
+
+/// Uniform point in a circle around the origin
+fn in_disc(radius: f32, rng: &mut SimRng) -> Vector2D {
+    let distance = radius * rng.gen_f32().sqrt();
+    Vector2D::new(distance, 0.0) * random_rotation(rng)
+}
+
+fn clamp_to_ground(pos: Vector2D, size: Vector2D) -> Vector2D {
+    Vector2D::new(pos.x().clamp(0.0, size.x()), pos.y().clamp(0.0, size.y()))
+}
@@ -0,0 +1,92 @@
+use std::fmt;
+use std::path::Path;
+
+use common::animals::ant::AntAction;
+use common::buildings::NestAction;
+use common::helper::config::Config;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::ground::Ground;
+use crate::persist::{self, PersistError};
+
+/// Decisions of all nests and ants in one tick, in the order of the nest and ant lists
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct TickRecord {
+    pub nests: Vec<NestAction>,
+    pub ants: Vec<AntAction>,
+}
+
+/// A recorded round: the config it started with, seed and world size included, and the decisions of every tick.
+///
+/// Playing it back repeats the round without any plugin. Config changes made while the round
+/// was recorded are not part of the replay.
+#[derive(Serialize, Deserialize)]
+pub struct Replay {
+    #[serde(with = "config_as_ron")]
+    pub config: Config,
+    pub ticks: Vec<TickRecord>,
+}
+
+impl Replay {
+    pub fn new(config: Config) -> Replay {
+        Replay {
+            config,
+            ticks: Vec::new(),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), PersistError> {
+        persist::save_binary(path, self)
+    }
+
+    pub fn load(path: &Path) -> Result<Replay, PersistError> {
+        let replay: Replay = persist::load_binary(path)?;
+        replay
+            .config
+            .validate()
+            .map_err(PersistError::InvalidConfig)?;
+        Ok(replay)
+    }
+
+    /// A new ground the ticks of this replay can be played on with [`Ground::replay_tick`]
+    pub fn ground(&self) -> Ground {
+        let mut ground = Ground::new(self.config.clone());
+        ground.start_new_round();
+        ground
+    }
+}
+
+/// Configs contain internally tagged enums, which the binary format cannot read, so the config
+/// of a replay is kept as RON text
+mod config_as_ron {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(config: &Config, serializer: S) -> Result<S::Ok, S::Error> {
+        let text = ron::to_string(config).map_err(serde::ser::Error::custom)?;
+        serializer.serialize_str(&text)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Config, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        ron::from_str(&text).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Reasons why a tick could not be played back
+#[derive(Debug)]
+pub enum ReplayError {
+    /// The recorded decisions do not fit the entities on the ground
+    Desync { tick: usize },
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Desync { tick } => {
+                write!(f, "replay does not match the world in tick {}", tick)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
@@ -0,0 +1,74 @@
+use std::path::Path;
+
+use common::helper::config::{Config, FoodSourceConfig, Placement, WorldConfig};
+use common::helper::Vector2D;
+use common::terrain::Obstacle;
+use serde::{Deserialize, Serialize};
+
+use crate::persist::{self, PersistError};
+
+/// A hand-made map: the world, where the nests are and where food grows back.
+///
+/// Everything else comes from `config`, values missing there keep their defaults. Random food
+/// can be turned off with `food.start_amount` and `food.spawn_time` set to 0.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Scenario {
+    pub name: String,
+    pub width: f32,
+    pub height: f32,
+    /// One nest at every position, the config decides when empty
+    pub nests: Vec<Vector2D>,
+    pub obstacles: Vec<Obstacle>,
+    pub food_sources: Vec<FoodSourceConfig>,
+    pub config: Config,
+}
+
+impl Default for Scenario {
+    fn default() -> Self {
+        let world = WorldConfig::new();
+        Scenario {
+            name: String::new(),
+            width: world.width,
+            height: world.height,
+            nests: Vec::new(),
+            obstacles: Vec::new(),
+            food_sources: Vec::new(),
+            config: Config::new(),
+        }
+    }
+}
+
+impl Scenario {
+    /// Reads a scenario from RON, or from TOML if `path` ends in `.toml`, and checks the config it results in
+    pub fn load(path: &Path) -> Result<Scenario, PersistError> {
+        let scenario: Scenario = if path.extension().is_some_and(|ext| ext == "toml") {
+            persist::load_toml(path)?
+        } else {
+            persist::load_ron(path)?
+        };
+        scenario
+            .to_config()
+            .validate()
+            .map_err(PersistError::InvalidConfig)?;
+        Ok(scenario)
+    }
+
+    /// The config rounds of this scenario run with
+    pub fn to_config(&self) -> Config {
+        let mut config = self.config.clone();
+        config.world = WorldConfig {
+            width: self.width,
+            height: self.height,
+            obstacles: self.obstacles.clone(),
+            food_sources: self.food_sources.clone(),
+        };
+        if !self.nests.is_empty() {
+            config.nests.colonies = self.nests.len() as u32;
+            config.nests.placement = Placement::Fixed {
+                points: self.nests.clone(),
+            };
+        }
+        config
+    }
+}
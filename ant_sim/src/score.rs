@@ -0,0 +1,157 @@
+use common::animals::ant::Ant;
+use common::buildings::context::NestContext;
+use common::buildings::Nest;
+use common::helper::config::ScoreConfig;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Metrics of one colony over a round, its satellite nests included
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColonyScore {
+    /// Id of the first nest of the colony, see [`Nest::colony`]
+    #[serde(alias = "nest_id")]
+    pub colony: usize,
+    pub food_delivered: u64,
+    pub ants_spawned: u64,
+    /// Sum over all ticks of the number of living ants of the colony
+    pub ant_ticks: u64,
+    /// Sum over all ticks of the energy of the nests of the colony
+    pub energy_integral: u64,
+    /// Number of ticks any nest of the colony was alive
+    pub survival_ticks: u64,
+    pub alive: bool,
+    #[serde(default)]
+    pub ants_lost: u64,
+    /// Ants of the colony alive at the end of the last tick
+    #[serde(default)]
+    pub living_ants: u64,
+    /// Food delivered in each of the last [`NestContext::RECENT_TICKS`] completed ticks, newest last
+    #[serde(default)]
+    recent_food: VecDeque<u32>,
+    /// Food delivered so far in the current tick
+    #[serde(default)]
+    food_this_tick: u32,
+}
+
+impl ColonyScore {
+    pub fn new(colony: usize) -> ColonyScore {
+        ColonyScore {
+            colony,
+            food_delivered: 0,
+            ants_spawned: 0,
+            ant_ticks: 0,
+            energy_integral: 0,
+            survival_ticks: 0,
+            alive: true,
+            ants_lost: 0,
+            living_ants: 0,
+            recent_food: VecDeque::new(),
+            food_this_tick: 0,
+        }
+    }
+
+    /// Food delivered to all nests of the colony in the last [`NestContext::RECENT_TICKS`]
+    /// completed ticks
+    pub fn recent_food(&self) -> u64 {
+        self.recent_food
+            .iter()
+            .map(|&amount| u64::from(amount))
+            .sum()
+    }
+
+    /// Weighted sum of all metrics
+    pub fn fitness(&self, weights: &ScoreConfig) -> f64 {
+        weights.food_delivered * self.food_delivered as f64
+            + weights.ants_spawned * self.ants_spawned as f64
+            + weights.ant_ticks * self.ant_ticks as f64
+            + weights.energy_integral * self.energy_integral as f64
+            + weights.survival_ticks * self.survival_ticks as f64
+    }
+}
+
+/// Collects the [`ColonyScore`]s of all colonies of a round, including colonies that already died
+#[derive(Default, Serialize, Deserialize)]
+pub struct Scoreboard {
+    #[serde(alias = "nests")]
+    colonies: Vec<ColonyScore>,
+}
+
+impl Scoreboard {
+    pub fn new() -> Scoreboard {
+        Scoreboard::default()
+    }
+
+    pub fn clear(&mut self) {
+        self.colonies.clear();
+    }
+
+    pub fn colonies(&self) -> &[ColonyScore] {
+        &self.colonies
+    }
+
+    pub fn get(&self, colony: usize) -> Option<&ColonyScore> {
+        self.colonies.iter().find(|s| s.colony == colony)
+    }
+
+    fn get_mut(&mut self, colony: usize) -> &mut ColonyScore {
+        let idx = match self.colonies.iter().position(|s| s.colony == colony) {
+            Some(idx) => idx,
+            None => {
+                self.colonies.push(ColonyScore::new(colony));
+                self.colonies.len() - 1
+            }
+        };
+        &mut self.colonies[idx]
+    }
+
+    pub fn record_colony(&mut self, colony: usize) {
+        self.get_mut(colony);
+    }
+
+    pub fn record_spawn(&mut self, colony: usize) {
+        self.get_mut(colony).ants_spawned += 1;
+    }
+
+    pub fn record_delivery(&mut self, colony: usize, amount: u32) {
+        let score = self.get_mut(colony);
+        score.food_delivered += u64::from(amount);
+        score.food_this_tick = score.food_this_tick.saturating_add(amount);
+    }
+
+    pub fn record_death(&mut self, colony: usize) {
+        self.get_mut(colony).ants_lost += 1;
+    }
+
+    /// Accumulates the per tick metrics, called once at the end of every tick
+    pub fn record_tick(&mut self, nests: &[Nest], ants: &[Ant]) {
+        for score in &mut self.colonies {
+            score.alive = false;
+            score.living_ants = 0;
+            score.recent_food.push_back(score.food_this_tick);
+            score.food_this_tick = 0;
+            if score.recent_food.len() > NestContext::RECENT_TICKS {
+                score.recent_food.pop_front();
+            }
+        }
+
+        // Satellite nests count for the colony they belong to
+        for nest in nests {
+            let score = self.get_mut(nest.colony);
+            if !score.alive {
+                score.alive = true;
+                score.survival_ticks += 1;
+            }
+            score.energy_integral += u64::from(nest.energy);
+        }
+
+        for ant in ants {
+            let score = self.get_mut(ant.colony);
+            score.ant_ticks += 1;
+            score.living_ants += 1;
+        }
+    }
+
+    pub fn total_food_delivered(&self) -> u64 {
+        self.colonies.iter().map(|s| s.food_delivered).sum()
+    }
+}
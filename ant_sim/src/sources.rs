@@ -0,0 +1,64 @@
+use common::helper::config::FoodSourceConfig;
+use common::helper::Vector2D;
+use common::items::food::FoodPellet;
+use serde::{Deserialize, Serialize};
+
+/// Food that grows back. Ants see and harvest it as an ordinary pellet, which the source
+/// refills every tick and replaces once it was eaten up.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct FoodSource {
+    pub position: Vector2D,
+    pub capacity: u32,
+    pub regrowth: u32,
+    /// Id of the pellet currently offered by the source
+    pellet: Option<usize>,
+}
+
+impl FoodSource {
+    pub fn new(config: &FoodSourceConfig) -> FoodSource {
+        FoodSource {
+            position: config.position,
+            capacity: config.capacity,
+            regrowth: config.regrowth,
+            pellet: None,
+        }
+    }
+
+    /// Grows by `amount`, creating a new pellet with id `next_food_id` if there is none.
+    /// Returns how much nutrition was added to the world.
+    pub fn grow(
+        &mut self,
+        amount: u32,
+        food: &mut Vec<FoodPellet>,
+        next_food_id: &mut usize,
+        bite_size: u32,
+    ) -> u32 {
+        let existing = self
+            .pellet
+            .and_then(|id| food.binary_search_by_key(&id, |f| f.id).ok());
+
+        match existing {
+            Some(idx) => {
+                let pellet = &mut food[idx];
+                let grown = amount.min(self.capacity.saturating_sub(pellet.nutrition));
+                pellet.nutrition += grown;
+                grown
+            }
+            None => {
+                let grown = amount.min(self.capacity);
+                if grown == 0 {
+                    return 0;
+                }
+                food.push(FoodPellet::new_at_pos(
+                    *next_food_id,
+                    self.position,
+                    grown,
+                    bite_size,
+                ));
+                self.pellet = Some(*next_food_id);
+                *next_food_id += 1;
+                grown
+            }
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use common::animals::ant::Ant;
+use common::helper::Vector2D;
+use common::items::food::FoodPellet;
+
+/// Uniform grid over the ground that remembers which ants and food pellets are in which cell,
+/// so range queries only have to look at the cells overlapping the range.
+///
+/// The grid stores indices into the ant and food lists of the ground together with the positions.
+/// It does not follow the ants, it has to be rebuilt whenever anything moved or the lists changed.
+pub struct SpatialGrid {
+    width: usize,
+    height: usize,
+    cell_size: f32,
+    ant_cells: Vec<Vec<(usize, Vector2D)>>,
+    food_cells: Vec<Vec<(usize, Vector2D)>>,
+}
+
+impl SpatialGrid {
+    pub fn new(size: Vector2D, cell_size: f32) -> SpatialGrid {
+        let cell_size = cell_size.max(1.0);
+        let width = (size.x() / cell_size).ceil().max(1.0) as usize;
+        let height = (size.y() / cell_size).ceil().max(1.0) as usize;
+
+        SpatialGrid {
+            width,
+            height,
+            cell_size,
+            ant_cells: vec![Vec::new(); width * height],
+            food_cells: vec![Vec::new(); width * height],
+        }
+    }
+
+    /// The grid of a ground without size, useless until it is replaced
+    pub fn empty() -> SpatialGrid {
+        SpatialGrid::new(Vector2D::new(0.0, 0.0), 1.0)
+    }
+
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    fn cell_coords(&self, pos: Vector2D) -> (usize, usize) {
+        let x = ((pos.x() / self.cell_size).max(0.0) as usize).min(self.width - 1);
+        let y = ((pos.y() / self.cell_size).max(0.0) as usize).min(self.height - 1);
+        (x, y)
+    }
+
+    fn cell_index(&self, pos: Vector2D) -> usize {
+        let (x, y) = self.cell_coords(pos);
+        y * self.width + x
+    }
+
+    /// Forgets everything and inserts all ants and food pellets again
+    pub fn rebuild(&mut self, ants: &[Ant], food: &[FoodPellet]) {
+        for cell in self.ant_cells.iter_mut().chain(self.food_cells.iter_mut()) {
+            cell.clear();
+        }
+
+        for (idx, ant) in ants.iter().enumerate() {
+            let cell = self.cell_index(ant.position);
+            self.ant_cells[cell].push((idx, ant.position));
+        }
+        for (idx, food_item) in food.iter().enumerate() {
+            self.insert_food(idx, food_item.get_position());
+        }
+    }
+
+    fn insert_food(&mut self, idx: usize, pos: Vector2D) {
+        let cell = self.cell_index(pos);
+        self.food_cells[cell].push((idx, pos));
+    }
+
+    /// Indices of all cells the square around `pos` with half side `radius` touches
+    fn cells_around(&self, pos: Vector2D, radius: f32) -> impl Iterator<Item = usize> {
+        let (min_x, min_y) = self.cell_coords(pos - Vector2D::new(radius, radius));
+        let (max_x, max_y) = self.cell_coords(pos + Vector2D::new(radius, radius));
+        let width = self.width;
+
+        (min_y..=max_y).flat_map(move |y| (min_x..=max_x).map(move |x| y * width + x))
+    }
+
+    /// Collects the indices of all ants closer than `radius` to `pos` into `found`, in ascending order
+    pub fn ants_within(&self, pos: Vector2D, radius: f32, found: &mut Vec<usize>) {
+        Self::collect(
+            &self.ant_cells,
+            self.cells_around(pos, radius),
+            pos,
+            radius,
+            found,
+        );
+    }
+
+    /// Collects the indices of all food pellets closer than `radius` to `pos` into `found`, in ascending order
+    pub fn food_within(&self, pos: Vector2D, radius: f32, found: &mut Vec<usize>) {
+        Self::collect(
+            &self.food_cells,
+            self.cells_around(pos, radius),
+            pos,
+            radius,
+            found,
+        );
+    }
+
+    fn collect(
+        cells: &[Vec<(usize, Vector2D)>],
+        around: impl Iterator<Item = usize>,
+        pos: Vector2D,
+        radius: f32,
+        found: &mut Vec<usize>,
+    ) {
+        found.clear();
+        for cell in around {
+            found.extend(
+                cells[cell]
+                    .iter()
+                    .filter(|(_, other)| other.distance(pos) < radius)
+                    .map(|&(idx, _)| idx),
+            );
+        }
+        found.sort_unstable();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::animals::ant::Caste;
+    use common::helper::config::AntConfig;
+    use common::helper::SimRng;
+
+    /// What the ground did before it had a grid
+    fn linear_scan(positions: &[Vector2D], pos: Vector2D, radius: f32) -> Vec<usize> {
+        (0..positions.len())
+            .filter(|&j| positions[j].distance(pos) < radius)
+            .collect()
+    }
+
+    /// Mostly on the ground, some on its edges and a few outside of it
+    fn random_pos(rng: &mut SimRng, size: Vector2D) -> Vector2D {
+        match rng.gen_range(0, 10) {
+            0 => Vector2D::new(0.0, rng.gen_f32() * size.y()),
+            1 => Vector2D::new(size.x(), size.y()),
+            2 => Vector2D::new(rng.gen_f32() * 1.4 - 0.2, rng.gen_f32() * 1.4 - 0.2) * size.x(),
+            _ => Vector2D::new(rng.gen_f32() * size.x(), rng.gen_f32() * size.y()),
+        }
+    }
+
+    #[test]
+    fn queries_match_a_linear_scan() {
+        let size = Vector2D::new(500.0, 300.0);
+        let config = AntConfig::new();
+
+        for seed in 0..20 {
+            let mut rng = SimRng::new(seed);
+            let ants: Vec<Ant> = (0..300)
+                .map(|id| {
+                    let pos = random_pos(&mut rng, size);
+                    Ant::new_at_pos(id, 0, 0, &config, Caste::Worker, pos)
+                })
+                .collect();
+            let food: Vec<FoodPellet> = (0..150)
+                .map(|id| FoodPellet::new_at_pos(id, random_pos(&mut rng, size), 10, 1))
+                .collect();
+            let ant_positions: Vec<Vector2D> = ants.iter().map(|ant| ant.position).collect();
+            let food_positions: Vec<Vector2D> = food.iter().map(|f| f.get_position()).collect();
+
+            let mut grid = SpatialGrid::new(size, 40.0);
+            grid.rebuild(&ants, &food);
+
+            let mut found = Vec::new();
+            for _ in 0..200 {
+                let pos = random_pos(&mut rng, size);
+                // Smaller and larger than a cell, up to more than the whole ground
+                for radius in [0.5, 7.0, 40.0, 95.0, 700.0] {
+                    grid.ants_within(pos, radius, &mut found);
+                    assert_eq!(found, linear_scan(&ant_positions, pos, radius));
+                    grid.food_within(pos, radius, &mut found);
+                    assert_eq!(found, linear_scan(&food_positions, pos, radius));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rebuild_forgets_the_old_positions() {
+        let size = Vector2D::new(100.0, 100.0);
+        let config = AntConfig::new();
+        let mut ant = Ant::new_at_pos(0, 0, 0, &config, Caste::Worker, Vector2D::new(10.0, 10.0));
+        let mut grid = SpatialGrid::new(size, 20.0);
+        grid.rebuild(&[ant], &[]);
+
+        ant.position = Vector2D::new(90.0, 90.0);
+        grid.rebuild(&[ant], &[]);
+
+        let mut found = Vec::new();
+        grid.ants_within(Vector2D::new(10.0, 10.0), 5.0, &mut found);
+        assert!(found.is_empty());
+        grid.ants_within(Vector2D::new(90.0, 90.0), 5.0, &mut found);
+        assert_eq!(found, [0]);
+    }
+}
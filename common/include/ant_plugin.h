@@ -0,0 +1,532 @@
+#pragma once
+
+/* Generated with cbindgen from the common crate, do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Bumped whenever the signature of a plugin function, or the meaning of a type crossing the
+ * plugin boundary changes without changing its layout
+ */
+#define ABI_VERSION 6
+
+#define PheromoneKind_COUNT 3
+
+/**
+ * What an ant is bred for, its stats come from the matching [`CasteConfig`]
+ */
+typedef enum {
+  Caste_Worker,
+  /**
+   * Fast and far-sighted, but carries little
+   */
+  Caste_Scout,
+  Caste_Soldier,
+  /**
+   * Slow, but carries a lot
+   */
+  Caste_Carrier,
+} Caste;
+
+/**
+ * Which food action an ant took
+ */
+typedef enum {
+  FoodAction_None,
+  FoodAction_Eat,
+  FoodAction_Carry,
+} FoodAction;
+
+typedef enum {
+  PheromoneKind_Home,
+  PheromoneKind_Food,
+  PheromoneKind_Alarm,
+} PheromoneKind;
+
+/**
+ * Why the last `SpawnAnts` of a nest spawned fewer ants than it asked for, or why its last
+ * `FoundNest` was refused
+ */
+typedef enum {
+  /**
+   * Every ant was spawned, or the nest did not ask for any
+   */
+  SpawnDenial_None,
+  /**
+   * Paying for another ant would have taken the energy down to the reserve
+   */
+  SpawnDenial_NotEnoughEnergy,
+  /**
+   * The nest already has as many living ants as it may have
+   */
+  SpawnDenial_PopulationLimit,
+  /**
+   * The site of the new nest is out of range, outside the world or inside an obstacle
+   */
+  SpawnDenial_InvalidSite,
+} SpawnDenial;
+
+typedef struct {
+  float _0[2];
+} Vector2D;
+
+typedef struct {
+  float _0[4];
+} Color;
+
+typedef struct {
+  float _0;
+} Rotation;
+
+typedef struct {
+  Vector2D position;
+  uint32_t energy;
+  size_t id;
+  /**
+   * Id of the colony, see [`Nest::colony`](crate::buildings::Nest::colony)
+   */
+  size_t colony;
+  /**
+   * Id of the nest this ant was spawned by
+   */
+  size_t nest;
+  Color color;
+  Rotation rotation;
+  Vector2D size;
+  float speed;
+  float angular_speed;
+  uint32_t max_energy;
+  float mouth_reach;
+  uint32_t rounds_to_energy_loss;
+  uint32_t carrying;
+  /**
+   * Ticks since the ant was spawned
+   */
+  uint32_t age;
+  /**
+   * Age at which the ant dies, however much energy it has
+   */
+  uint32_t lifespan;
+  Caste caste;
+  float vision_range;
+  /**
+   * Most food the ant can carry at once
+   */
+  uint32_t carry_capacity;
+} Ant;
+
+typedef struct {
+  Vector2D position;
+  uint32_t nutrition;
+  uint32_t bite_size;
+  size_t id;
+} FoodPellet;
+
+typedef enum {
+  AntAction_Nothing,
+  AntAction_RotateLeft,
+  AntAction_RotateRight,
+  AntAction_GoForward,
+  AntAction_EatFood,
+  AntAction_CarryFood,
+  AntAction_UnloadFood,
+  AntAction_DepositPheromone,
+} AntAction_Tag;
+
+typedef struct {
+  PheromoneKind _0;
+  float _1;
+} AntAction_DepositPheromone_Body;
+
+typedef struct {
+  AntAction_Tag tag;
+  union {
+    struct {
+      float rotate_left;
+    };
+    struct {
+      float rotate_right;
+    };
+    struct {
+      float go_forward;
+    };
+    struct {
+      FoodPellet eat_food;
+    };
+    struct {
+      FoodPellet carry_food;
+    };
+    AntAction_DepositPheromone_Body deposit_pheromone;
+  };
+} AntAction;
+
+typedef enum {
+  SeenObject_Ant,
+  SeenObject_Food,
+} SeenObject_Tag;
+
+typedef struct {
+  SeenObject_Tag tag;
+  union {
+    struct {
+      Ant ant;
+    };
+    struct {
+      FoodPellet food;
+    };
+  };
+} SeenObject;
+
+typedef struct {
+  SeenObject object;
+  float distance;
+} Vision;
+
+/**
+ * Read only view of a slice with a stable layout, so it can cross the plugin boundary
+ */
+typedef struct {
+  const Vision *ptr;
+  size_t len;
+} FfiSlice_Vision;
+
+/**
+ * Pheromone strength of one kind at three points in front of the ant
+ */
+typedef struct {
+  float left;
+  float center;
+  float right;
+} PheromoneSample;
+
+/**
+ * Everything an ant smells in one tick, one sample per pheromone kind
+ */
+typedef struct {
+  PheromoneSample samples[PheromoneKind_COUNT];
+} PheromoneSense;
+
+/**
+ * View of the memory block the host keeps for every ant.
+ *
+ * The size is declared by the plugin through `ant_memory_size`, the block is zeroed when the
+ * ant is spawned and freed when the ant dies. It is aligned to 8 bytes.
+ */
+typedef struct {
+  uint8_t *data;
+  size_t len;
+} AntMemory;
+#define AntMemory_ALIGN 8
+
+/**
+ * Small deterministic random number generator (SplitMix64).
+ *
+ * The host seeds all of its randomness from `GeneralConfig::seed` and hands
+ * a fresh generator to the plugins each tick, so a round can be replayed
+ * exactly by running it again with the same seed.
+ */
+typedef struct {
+  uint64_t state;
+} SimRng;
+
+/**
+ * What an ant got out of its `EatFood` or `CarryFood` action.
+ *
+ * If several ants want the same pellet in one tick, the ant that was spawned first is served first,
+ * the others share what is left in the same order.
+ */
+typedef struct {
+  FoodAction action;
+  size_t food_id;
+  /**
+   * What the ant would have got if it had been alone at the pellet
+   */
+  uint32_t requested;
+  uint32_t received;
+} FoodOutcome;
+
+/**
+ * Everything the host tells a plugin about the surroundings of an ant in one tick
+ */
+typedef struct {
+  /**
+   * Ants and food within vision range
+   */
+  FfiSlice_Vision vision;
+  PheromoneSense pheromones;
+  /**
+   * The ant's own memory block, see [`AntMemory`]
+   */
+  AntMemory memory;
+  /**
+   * Generator for this ant and tick, derived from the round seed
+   */
+  SimRng rng;
+  /**
+   * Ticks since the round started
+   */
+  uint64_t tick;
+  /**
+   * Result of the food action of the previous tick, `FoodAction::None` if there was none
+   */
+  FoodOutcome food_outcome;
+  /**
+   * The nest of the ant called its ants home in this tick
+   */
+  bool recalled;
+  /**
+   * Signal a nest of the colony broadcast in this tick, 0 if there was none
+   */
+  uint32_t signal;
+} AntContext;
+
+typedef struct {
+  size_t id;
+  /**
+   * Id of the first nest of the colony, satellite nests share it
+   */
+  size_t colony;
+  Vector2D pos;
+  uint32_t energy;
+  uint32_t rounds_to_energy_loss;
+  /**
+   * Outcome of the nest action of the previous tick
+   */
+  SpawnDenial spawn_denied;
+  /**
+   * Energy put aside, it decays slower but cannot be spent before it is taken out again
+   */
+  uint32_t granary;
+} Nest;
+
+typedef enum {
+  NestAction_Nothing,
+  /**
+   * Spawns that many ants of the caste, as long as the nest can pay for them
+   */
+  NestAction_SpawnAnts,
+  /**
+   * Founds a satellite nest of the colony at the position and hands it that much energy
+   */
+  NestAction_FoundNest,
+  /**
+   * Moves up to that much energy into the granary
+   */
+  NestAction_StoreFood,
+  /**
+   * Takes up to that much energy out of the granary
+   */
+  NestAction_TakeFood,
+  /**
+   * Calls the ants of this nest home, they see it in their next update
+   */
+  NestAction_RecallAnts,
+  /**
+   * Sends a signal to every ant of the colony, they see it in their next update
+   */
+  NestAction_Broadcast,
+} NestAction_Tag;
+
+typedef struct {
+  size_t _0;
+  Caste _1;
+} NestAction_SpawnAnts_Body;
+
+typedef struct {
+  Vector2D _0;
+  uint32_t _1;
+} NestAction_FoundNest_Body;
+
+typedef struct {
+  NestAction_Tag tag;
+  union {
+    NestAction_SpawnAnts_Body spawn_ants;
+    NestAction_FoundNest_Body found_nest;
+    struct {
+      uint32_t store_food;
+    };
+    struct {
+      uint32_t take_food;
+    };
+    struct {
+      uint32_t broadcast;
+    };
+  };
+} NestAction;
+
+/**
+ * Everything the host tells a plugin about the colony of a nest in one tick
+ */
+typedef struct {
+  /**
+   * Generator for this nest and tick, derived from the round seed
+   */
+  SimRng rng;
+  /**
+   * Ticks since the round started
+   */
+  uint64_t tick;
+  /**
+   * Ants of the colony alive at the end of the previous tick
+   */
+  uint32_t living_ants;
+  /**
+   * Food delivered to all nests of the colony in the last [`NestContext::RECENT_TICKS`] ticks,
+   * not counting the current one
+   */
+  uint32_t recent_food;
+  /**
+   * Ants of the colony that died since the round started
+   */
+  uint32_t ants_lost;
+} NestContext;
+#define NestContext_RECENT_TICKS 100
+
+
+
+/* Functions a plugin has to export. ant_memory_size is optional. */
+uint32_t abi_version(void);
+uint64_t abi_layout_hash(void);
+AntAction ant_update(const Ant *ant, AntContext *context);
+NestAction nest_update(const Nest *nest, NestContext *context);
+void reset(void);
+size_t ant_memory_size(void);
+
+/* Same hash as common::abi::LAYOUT_HASH, return it from abi_layout_hash. */
+#define ANT_LAYOUT_STEP(h, v) (((h) ^ (uint64_t)(v)) * 0x00000100000001b3ull)
+#define ANT_LAYOUT_TYPE(h, T) ANT_LAYOUT_STEP(ANT_LAYOUT_STEP(h, sizeof(T)), _Alignof(T))
+#define ANT_LAYOUT_FIELD(h, T, f) ANT_LAYOUT_STEP(h, offsetof(T, f))
+static inline uint64_t ant_plugin_layout_hash(void) {
+    uint64_t h = 0xcbf29ce484222325ull;
+    h = ANT_LAYOUT_TYPE(h, Ant);
+    h = ANT_LAYOUT_FIELD(h, Ant, position);
+    h = ANT_LAYOUT_FIELD(h, Ant, energy);
+    h = ANT_LAYOUT_FIELD(h, Ant, id);
+    h = ANT_LAYOUT_FIELD(h, Ant, colony);
+    h = ANT_LAYOUT_FIELD(h, Ant, nest);
+    h = ANT_LAYOUT_FIELD(h, Ant, color);
+    h = ANT_LAYOUT_FIELD(h, Ant, rotation);
+    h = ANT_LAYOUT_FIELD(h, Ant, size);
+    h = ANT_LAYOUT_FIELD(h, Ant, speed);
+    h = ANT_LAYOUT_FIELD(h, Ant, angular_speed);
+    h = ANT_LAYOUT_FIELD(h, Ant, max_energy);
+    h = ANT_LAYOUT_FIELD(h, Ant, mouth_reach);
+    h = ANT_LAYOUT_FIELD(h, Ant, rounds_to_energy_loss);
+    h = ANT_LAYOUT_FIELD(h, Ant, carrying);
+    h = ANT_LAYOUT_FIELD(h, Ant, age);
+    h = ANT_LAYOUT_FIELD(h, Ant, lifespan);
+    h = ANT_LAYOUT_FIELD(h, Ant, caste);
+    h = ANT_LAYOUT_FIELD(h, Ant, vision_range);
+    h = ANT_LAYOUT_FIELD(h, Ant, carry_capacity);
+    h = ANT_LAYOUT_TYPE(h, AntAction);
+    h = ANT_LAYOUT_STEP(h, AntAction_Nothing);
+    h = ANT_LAYOUT_STEP(h, AntAction_RotateLeft);
+    h = ANT_LAYOUT_FIELD(h, AntAction, rotate_left);
+    h = ANT_LAYOUT_STEP(h, AntAction_RotateRight);
+    h = ANT_LAYOUT_FIELD(h, AntAction, rotate_right);
+    h = ANT_LAYOUT_STEP(h, AntAction_GoForward);
+    h = ANT_LAYOUT_FIELD(h, AntAction, go_forward);
+    h = ANT_LAYOUT_STEP(h, AntAction_EatFood);
+    h = ANT_LAYOUT_FIELD(h, AntAction, eat_food);
+    h = ANT_LAYOUT_STEP(h, AntAction_CarryFood);
+    h = ANT_LAYOUT_FIELD(h, AntAction, carry_food);
+    h = ANT_LAYOUT_STEP(h, AntAction_UnloadFood);
+    h = ANT_LAYOUT_STEP(h, AntAction_DepositPheromone);
+    h = ANT_LAYOUT_FIELD(h, AntAction, deposit_pheromone._0);
+    h = ANT_LAYOUT_FIELD(h, AntAction, deposit_pheromone._1);
+    h = ANT_LAYOUT_TYPE(h, AntContext);
+    h = ANT_LAYOUT_FIELD(h, AntContext, vision);
+    h = ANT_LAYOUT_FIELD(h, AntContext, pheromones);
+    h = ANT_LAYOUT_FIELD(h, AntContext, memory);
+    h = ANT_LAYOUT_FIELD(h, AntContext, rng);
+    h = ANT_LAYOUT_FIELD(h, AntContext, tick);
+    h = ANT_LAYOUT_FIELD(h, AntContext, food_outcome);
+    h = ANT_LAYOUT_FIELD(h, AntContext, recalled);
+    h = ANT_LAYOUT_FIELD(h, AntContext, signal);
+    h = ANT_LAYOUT_TYPE(h, AntMemory);
+    h = ANT_LAYOUT_FIELD(h, AntMemory, data);
+    h = ANT_LAYOUT_FIELD(h, AntMemory, len);
+    h = ANT_LAYOUT_TYPE(h, FfiSlice_Vision);
+    h = ANT_LAYOUT_FIELD(h, FfiSlice_Vision, ptr);
+    h = ANT_LAYOUT_FIELD(h, FfiSlice_Vision, len);
+    h = ANT_LAYOUT_TYPE(h, Nest);
+    h = ANT_LAYOUT_FIELD(h, Nest, id);
+    h = ANT_LAYOUT_FIELD(h, Nest, colony);
+    h = ANT_LAYOUT_FIELD(h, Nest, pos);
+    h = ANT_LAYOUT_FIELD(h, Nest, energy);
+    h = ANT_LAYOUT_FIELD(h, Nest, rounds_to_energy_loss);
+    h = ANT_LAYOUT_FIELD(h, Nest, spawn_denied);
+    h = ANT_LAYOUT_FIELD(h, Nest, granary);
+    h = ANT_LAYOUT_TYPE(h, NestAction);
+    h = ANT_LAYOUT_STEP(h, NestAction_Nothing);
+    h = ANT_LAYOUT_STEP(h, NestAction_SpawnAnts);
+    h = ANT_LAYOUT_FIELD(h, NestAction, spawn_ants._0);
+    h = ANT_LAYOUT_FIELD(h, NestAction, spawn_ants._1);
+    h = ANT_LAYOUT_STEP(h, NestAction_FoundNest);
+    h = ANT_LAYOUT_FIELD(h, NestAction, found_nest._0);
+    h = ANT_LAYOUT_FIELD(h, NestAction, found_nest._1);
+    h = ANT_LAYOUT_STEP(h, NestAction_StoreFood);
+    h = ANT_LAYOUT_FIELD(h, NestAction, store_food);
+    h = ANT_LAYOUT_STEP(h, NestAction_TakeFood);
+    h = ANT_LAYOUT_FIELD(h, NestAction, take_food);
+    h = ANT_LAYOUT_STEP(h, NestAction_RecallAnts);
+    h = ANT_LAYOUT_STEP(h, NestAction_Broadcast);
+    h = ANT_LAYOUT_FIELD(h, NestAction, broadcast);
+    h = ANT_LAYOUT_TYPE(h, NestContext);
+    h = ANT_LAYOUT_FIELD(h, NestContext, rng);
+    h = ANT_LAYOUT_FIELD(h, NestContext, tick);
+    h = ANT_LAYOUT_FIELD(h, NestContext, living_ants);
+    h = ANT_LAYOUT_FIELD(h, NestContext, recent_food);
+    h = ANT_LAYOUT_FIELD(h, NestContext, ants_lost);
+    h = ANT_LAYOUT_TYPE(h, Vision);
+    h = ANT_LAYOUT_FIELD(h, Vision, object);
+    h = ANT_LAYOUT_FIELD(h, Vision, distance);
+    h = ANT_LAYOUT_TYPE(h, SeenObject);
+    h = ANT_LAYOUT_STEP(h, SeenObject_Ant);
+    h = ANT_LAYOUT_FIELD(h, SeenObject, ant);
+    h = ANT_LAYOUT_STEP(h, SeenObject_Food);
+    h = ANT_LAYOUT_FIELD(h, SeenObject, food);
+    h = ANT_LAYOUT_TYPE(h, FoodPellet);
+    h = ANT_LAYOUT_FIELD(h, FoodPellet, position);
+    h = ANT_LAYOUT_FIELD(h, FoodPellet, nutrition);
+    h = ANT_LAYOUT_FIELD(h, FoodPellet, bite_size);
+    h = ANT_LAYOUT_FIELD(h, FoodPellet, id);
+    h = ANT_LAYOUT_TYPE(h, FoodAction);
+    h = ANT_LAYOUT_STEP(h, FoodAction_None);
+    h = ANT_LAYOUT_STEP(h, FoodAction_Eat);
+    h = ANT_LAYOUT_STEP(h, FoodAction_Carry);
+    h = ANT_LAYOUT_TYPE(h, FoodOutcome);
+    h = ANT_LAYOUT_FIELD(h, FoodOutcome, action);
+    h = ANT_LAYOUT_FIELD(h, FoodOutcome, food_id);
+    h = ANT_LAYOUT_FIELD(h, FoodOutcome, requested);
+    h = ANT_LAYOUT_FIELD(h, FoodOutcome, received);
+    h = ANT_LAYOUT_TYPE(h, PheromoneKind);
+    h = ANT_LAYOUT_STEP(h, PheromoneKind_Home);
+    h = ANT_LAYOUT_STEP(h, PheromoneKind_Food);
+    h = ANT_LAYOUT_STEP(h, PheromoneKind_Alarm);
+    h = ANT_LAYOUT_TYPE(h, PheromoneSample);
+    h = ANT_LAYOUT_FIELD(h, PheromoneSample, left);
+    h = ANT_LAYOUT_FIELD(h, PheromoneSample, center);
+    h = ANT_LAYOUT_FIELD(h, PheromoneSample, right);
+    h = ANT_LAYOUT_TYPE(h, PheromoneSense);
+    h = ANT_LAYOUT_FIELD(h, PheromoneSense, samples);
+    h = ANT_LAYOUT_TYPE(h, SimRng);
+    h = ANT_LAYOUT_TYPE(h, Vector2D);
+    h = ANT_LAYOUT_TYPE(h, Rotation);
+    h = ANT_LAYOUT_TYPE(h, Color);
+    h = ANT_LAYOUT_TYPE(h, Caste);
+    h = ANT_LAYOUT_STEP(h, Caste_Worker);
+    h = ANT_LAYOUT_STEP(h, Caste_Scout);
+    h = ANT_LAYOUT_STEP(h, Caste_Soldier);
+    h = ANT_LAYOUT_STEP(h, Caste_Carrier);
+    h = ANT_LAYOUT_TYPE(h, SpawnDenial);
+    h = ANT_LAYOUT_STEP(h, SpawnDenial_None);
+    h = ANT_LAYOUT_STEP(h, SpawnDenial_NotEnoughEnergy);
+    h = ANT_LAYOUT_STEP(h, SpawnDenial_PopulationLimit);
+    h = ANT_LAYOUT_STEP(h, SpawnDenial_InvalidSite);
+    return h;
+}
@@ -0,0 +1,263 @@
+//! Version information a plugin has to export, so the host can refuse libraries
+//! that were built against a different `common`.
+//!
+//! Plugins export it with [`export_abi!`](crate::export_abi).
+
+use std::ffi::c_int;
+use std::mem::{align_of, offset_of, size_of, MaybeUninit};
+
+use crate::animals::ant::{Ant, AntAction, Caste};
+use crate::animals::context::AntContext;
+use crate::animals::memory::AntMemory;
+use crate::buildings::context::NestContext;
+use crate::buildings::{Nest, NestAction, SpawnDenial};
+use crate::helper::{Color, FfiSlice, Rotation, SeenObject, SimRng, Vector2D, Vision};
+use crate::items::food::{FoodAction, FoodOutcome, FoodPellet};
+use crate::pheromones::{PheromoneKind, PheromoneSample, PheromoneSense};
+
+/// Bumped whenever the signature of a plugin function, or the meaning of a type crossing the
+/// plugin boundary changes without changing its layout
+pub const ABI_VERSION: u32 = 6;
+
+/// Hash over size, alignment and field offsets of every type crossing the plugin boundary, and
+/// over the discriminants of its enums
+pub const LAYOUT_HASH: u64 = layout_hash();
+
+/// FNV-1a over the layout numbers, one at a time
+struct LayoutHasher(u64);
+
+impl LayoutHasher {
+    const fn add(self, value: usize) -> LayoutHasher {
+        LayoutHasher((self.0 ^ value as u64).wrapping_mul(0x0000_0100_0000_01b3))
+    }
+}
+
+/// Value with every byte 0, only used for types where that is valid
+const fn zeroed<T>() -> T {
+    unsafe { MaybeUninit::zeroed().assume_init() }
+}
+
+/// Discriminant of a `#[repr(C)]` enum with fields, which starts with a C `int` tag
+const fn tag<T>(value: &T) -> usize {
+    unsafe { *(value as *const T as *const c_int) as usize }
+}
+
+/// Offset of a field inside an enum variant, `offset_of!` only works for structs
+const fn offset<T, F>(value: &T, field: &F) -> usize {
+    unsafe { (field as *const F as *const u8).offset_from(value as *const T as *const u8) as usize }
+}
+
+/// Adds size and alignment of a type, then the offsets of the listed fields. Wrappers with a
+/// single field have nothing but their size and alignment to add.
+macro_rules! add_type {
+    ($hash:expr, $ty:ty $(, $field:ident)*) => {
+        $hash
+            .add(size_of::<$ty>())
+            .add(align_of::<$ty>())
+            $(.add(offset_of!($ty, $field)))*
+    };
+}
+
+/// Adds size and alignment of an enum without fields, then the discriminants of its variants
+macro_rules! add_enum {
+    ($hash:expr, $ty:ident $(, $variant:ident)*) => {
+        $hash
+            .add(size_of::<$ty>())
+            .add(align_of::<$ty>())
+            $(.add($ty::$variant as usize))*
+    };
+}
+
+/// Adds the tag of one variant of an enum with fields, then the offsets of its fields
+macro_rules! add_variant {
+    ($hash:expr, $ty:ident :: $variant:ident) => {
+        $hash.add(tag(&$ty::$variant))
+    };
+    ($hash:expr, $ty:ident :: $variant:ident ($($field:ident),+)) => {{
+        let value = $ty::$variant($({ let $field = zeroed(); $field }),+);
+        match &value {
+            $ty::$variant($($field),+) => $hash.add(tag(&value))$(.add(offset(&value, $field)))+,
+            #[allow(unreachable_patterns)]
+            _ => unreachable!(),
+        }
+    }};
+}
+
+// Keep in sync with `ant_plugin_layout_hash` in the trailer of cbindgen.toml
+const fn layout_hash() -> u64 {
+    let hash = LayoutHasher(0xcbf2_9ce4_8422_2325);
+    let hash = add_type!(
+        hash,
+        Ant,
+        position,
+        energy,
+        id,
+        colony,
+        nest,
+        color,
+        rotation,
+        size,
+        speed,
+        angular_speed,
+        max_energy,
+        mouth_reach,
+        rounds_to_energy_loss,
+        carrying,
+        age,
+        lifespan,
+        caste,
+        vision_range,
+        carry_capacity
+    );
+
+    let hash = add_type!(hash, AntAction);
+    let hash = add_variant!(hash, AntAction::Nothing);
+    let hash = add_variant!(hash, AntAction::RotateLeft(angle));
+    let hash = add_variant!(hash, AntAction::RotateRight(angle));
+    let hash = add_variant!(hash, AntAction::GoForward(length));
+    let hash = add_variant!(hash, AntAction::EatFood(food));
+    let hash = add_variant!(hash, AntAction::CarryFood(food));
+    let hash = add_variant!(hash, AntAction::UnloadFood);
+    let hash = add_variant!(hash, AntAction::DepositPheromone(kind, amount));
+
+    let hash = add_type!(
+        hash,
+        AntContext,
+        vision,
+        pheromones,
+        memory,
+        rng,
+        tick,
+        food_outcome,
+        recalled,
+        signal
+    );
+    let hash = add_type!(hash, AntMemory, data, len);
+    let hash = add_type!(hash, FfiSlice<Vision>, ptr, len);
+    let hash = add_type!(
+        hash,
+        Nest,
+        id,
+        colony,
+        pos,
+        energy,
+        rounds_to_energy_loss,
+        spawn_denied,
+        granary
+    );
+
+    let hash = add_type!(hash, NestAction);
+    let hash = add_variant!(hash, NestAction::Nothing);
+    let hash = add_variant!(hash, NestAction::SpawnAnts(amount, caste));
+    let hash = add_variant!(hash, NestAction::FoundNest(position, energy));
+    let hash = add_variant!(hash, NestAction::StoreFood(amount));
+    let hash = add_variant!(hash, NestAction::TakeFood(amount));
+    let hash = add_variant!(hash, NestAction::RecallAnts);
+    let hash = add_variant!(hash, NestAction::Broadcast(signal));
+
+    let hash = add_type!(
+        hash,
+        NestContext,
+        rng,
+        tick,
+        living_ants,
+        recent_food,
+        ants_lost
+    );
+    let hash = add_type!(hash, Vision, object, distance);
+
+    let hash = add_type!(hash, SeenObject);
+    let hash = add_variant!(hash, SeenObject::Ant(ant));
+    let hash = add_variant!(hash, SeenObject::Food(food));
+
+    let hash = add_type!(hash, FoodPellet, position, nutrition, bite_size, id);
+    let hash = add_enum!(hash, FoodAction, None, Eat, Carry);
+    let hash = add_type!(hash, FoodOutcome, action, food_id, requested, received);
+    let hash = add_enum!(hash, PheromoneKind, Home, Food, Alarm);
+    let hash = add_type!(hash, PheromoneSample, left, center, right);
+    let hash = add_type!(hash, PheromoneSense, samples);
+    let hash = add_type!(hash, SimRng);
+    let hash = add_type!(hash, Vector2D);
+    let hash = add_type!(hash, Rotation);
+    let hash = add_type!(hash, Color);
+    let hash = add_enum!(hash, Caste, Worker, Scout, Soldier, Carrier);
+    let hash = add_enum!(
+        hash,
+        SpawnDenial,
+        None,
+        NotEnoughEnergy,
+        PopulationLimit,
+        InvalidSite
+    );
+    hash.0
+}
+
+pub type AbiVersionFn = extern "C" fn() -> u32;
+pub type LayoutHashFn = extern "C" fn() -> u64;
+
+/// Exports `abi_version` and `abi_layout_hash` from a plugin, built from the `common` it is compiled against
+#[macro_export]
+macro_rules! export_abi {
+    () => {
+        #[no_mangle]
+        pub extern "C" fn abi_version() -> u32 {
+            $crate::abi::ABI_VERSION
+        }
+
+        #[no_mangle]
+        pub extern "C" fn abi_layout_hash() -> u64 {
+            $crate::abi::LAYOUT_HASH
+        }
+    };
+}
+
+#[derive(Debug)]
+pub enum AbiError {
+    MissingSymbol(&'static str),
+    VersionMismatch { plugin: u32, host: u32 },
+    LayoutMismatch { plugin: u64, host: u64 },
+}
+
+impl std::fmt::Display for AbiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            AbiError::MissingSymbol(name) => write!(f, "plugin does not export `{}`", name),
+            AbiError::VersionMismatch { plugin, host } => write!(
+                f,
+                "plugin was built for ABI version {}, the simulator uses {}",
+                plugin, host
+            ),
+            AbiError::LayoutMismatch { plugin, host } => write!(
+                f,
+                "plugin was built against a different common crate (layout {:016x}, expected {:016x}), rebuild it",
+                plugin, host
+            ),
+        }
+    }
+}
+
+impl std::error::Error for AbiError {}
+
+/// Compares what a plugin exported with the host. `None` means the symbol was not found.
+pub fn check(
+    version: Option<AbiVersionFn>,
+    layout_hash: Option<LayoutHashFn>,
+) -> Result<(), AbiError> {
+    let version = version.ok_or(AbiError::MissingSymbol("abi_version"))?();
+    if version != ABI_VERSION {
+        return Err(AbiError::VersionMismatch {
+            plugin: version,
+            host: ABI_VERSION,
+        });
+    }
+
+    let layout_hash = layout_hash.ok_or(AbiError::MissingSymbol("abi_layout_hash"))?();
+    if layout_hash != LAYOUT_HASH {
+        return Err(AbiError::LayoutMismatch {
+            plugin: layout_hash,
+            host: LAYOUT_HASH,
+        });
+    }
+
+    Ok(())
+}
@@ -1,8 +1,12 @@
 use crate::helper::config::AntConfig;
 use crate::helper::*;
 use crate::items::food::FoodPellet;
+use crate::pheromones::PheromoneKind;
+use crate::terrain::Obstacle;
+use serde::{Deserialize, Serialize};
 
 #[repr(C)]
+#[derive(Clone, Copy, Serialize, Deserialize)]
 pub enum AntAction {
     Nothing,
     RotateLeft(f32),
@@ -11,6 +15,7 @@ pub enum AntAction {
     EatFood(FoodPellet),
     CarryFood(FoodPellet),
     UnloadFood,
+    DepositPheromone(PheromoneKind, f32),
 }
 
 impl std::fmt::Display for AntAction {
@@ -23,16 +28,54 @@ impl std::fmt::Display for AntAction {
             AntAction::EatFood(_) => write!(f, "EatFood"),
             AntAction::CarryFood(_) => write!(f, "CarryFood"),
             AntAction::UnloadFood => write!(f, "UnloadFood"),
+            AntAction::DepositPheromone(kind, amount) => {
+                write!(f, "DepositPheromone({:?}, {})", kind, amount)
+            }
         }
     }
 }
 
+/// What an ant is bred for, its stats come from the matching [`CasteConfig`]
 #[repr(C)]
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Caste {
+    Worker,
+    /// Fast and far-sighted, but carries little
+    Scout,
+    Soldier,
+    /// Slow, but carries a lot
+    Carrier,
+}
+
+impl Caste {
+    pub const ALL: [Caste; 4] = [Caste::Worker, Caste::Scout, Caste::Soldier, Caste::Carrier];
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+}
+
+impl std::fmt::Display for Caste {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Caste::Worker => write!(f, "Worker"),
+            Caste::Scout => write!(f, "Scout"),
+            Caste::Soldier => write!(f, "Soldier"),
+            Caste::Carrier => write!(f, "Carrier"),
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Serialize, Deserialize)]
 pub struct Ant {
     pub position: Vector2D,
     pub energy: u32,
     pub id: usize,
+    /// Id of the colony, see [`Nest::colony`](crate::buildings::Nest::colony)
+    pub colony: usize,
+    /// Id of the nest this ant was spawned by
+    pub nest: usize,
     pub color: Color,
     pub rotation: Rotation,
     pub size: Vector2D,
@@ -42,17 +85,80 @@ pub struct Ant {
     pub mouth_reach: f32,
     pub rounds_to_energy_loss: u32,
     pub carrying: u32,
+    /// Ticks since the ant was spawned
+    pub age: u32,
+    /// Age at which the ant dies, however much energy it has
+    pub lifespan: u32,
+    pub caste: Caste,
+    pub vision_range: f32,
+    /// Most food the ant can carry at once
+    pub carry_capacity: u32,
 }
 
 impl Ant {
+    pub fn new_at_pos(
+        id: usize,
+        colony: usize,
+        nest: usize,
+        config: &AntConfig,
+        caste: Caste,
+        position: Vector2D,
+    ) -> Ant {
+        let stats = config.caste(caste);
+        Ant {
+            id,
+            colony,
+            nest,
+            position,
+            color: RED,
+            rotation: Rotation::new_rad(0.0),
+            size: Vector2D::new(16.0, 7.0),
+            speed: stats.speed,
+            angular_speed: config.angular_speed,
+            energy: config.max_energy,
+            max_energy: config.max_energy,
+            mouth_reach: config.mouth_reach,
+            rounds_to_energy_loss: config.energy_loss_rounds,
+            carrying: 0,
+            age: 0,
+            lifespan: config.lifespan,
+            caste,
+            vision_range: stats.vision_range,
+            carry_capacity: stats.carry_capacity,
+        }
+    }
+
     pub fn is_alive(&self) -> bool {
-        self.energy != 0
+        self.energy != 0 && !self.is_too_old()
+    }
+
+    pub fn is_too_old(&self) -> bool {
+        self.age >= self.lifespan
+    }
+
+    pub fn is_same_colony(&self, other: &Ant) -> bool {
+        self.colony == other.colony
     }
 }
 
 // Actions
 impl Ant {
-    pub fn eat_food(&mut self, food: &mut FoodPellet) {
+    /// How much one bite of `food` would give, without eating more than fits into the ant
+    pub fn bite_wanted(&self, food: &FoodPellet) -> u32 {
+        food.bite_size
+            .min(self.max_energy.saturating_sub(self.energy))
+            .min(food.nutrition)
+    }
+
+    /// How much of `food` the ant could pick up
+    pub fn carry_wanted(&self, food: &FoodPellet) -> u32 {
+        self.carry_capacity
+            .saturating_sub(self.carrying)
+            .min(food.nutrition)
+    }
+
+    /// Returns the amount eaten
+    pub fn eat_food(&mut self, food: &mut FoodPellet) -> u32 {
         // let food_pos = food.borrow().get_position();
         // let food_dir = food_pos - self.get_mouth_position();
         // let dist = food_dir.length();
@@ -61,15 +167,16 @@ impl Ant {
         //     self.energy += food.borrow_mut().get_eaten();
         //     self.energy = self.energy.min(self.max_energy);
         // }
-        self.energy += food.get_eaten();
-        self.energy = self.energy.min(self.max_energy);
+        let eaten = food.take(self.bite_wanted(food));
+        self.energy += eaten;
+        eaten
     }
 
-    pub fn carry_food(&mut self, food: &mut FoodPellet, config: &AntConfig) {
-        let capacity_left = config.carry_capacity - self.carrying;
-        let amount_got = u32::min(capacity_left, food.nutrition);
-        food.nutrition -= amount_got;
+    /// Returns the amount picked up
+    pub fn carry_food(&mut self, food: &mut FoodPellet) -> u32 {
+        let amount_got = food.take(self.carry_wanted(food));
         self.carrying += amount_got;
+        amount_got
     }
 
     /// Removes the carried food from the ant
@@ -79,13 +186,23 @@ impl Ant {
         carrying
     }
 
-    pub fn go_forward(&mut self, length: f32) {
+    /// Does not move at all if the way is blocked by one of the `obstacles`
+    pub fn go_forward(&mut self, length: f32, obstacles: &[Obstacle]) {
         let movement_amount = self.speed.min(length).max(-self.speed);
 
-        self.position += Vector2D::new(
-            self.rotation.get_rad().cos(),
-            -self.rotation.get_rad().sin(),
-        ) * movement_amount;
+        let target = self.position
+            + Vector2D::new(
+                self.rotation.get_rad().cos(),
+                -self.rotation.get_rad().sin(),
+            ) * movement_amount;
+
+        // An ant that somehow ended up inside an obstacle may walk out of it
+        let blocked = obstacles.iter().any(|obstacle| {
+            !obstacle.contains(self.position) && obstacle.blocks(self.position, target)
+        });
+        if !blocked {
+            self.position = target;
+        }
     }
 
     pub fn rotate_left(&mut self, angle: f32) {
@@ -0,0 +1,24 @@
+use crate::animals::memory::AntMemory;
+use crate::helper::{FfiSlice, SimRng, Vision};
+use crate::items::food::FoodOutcome;
+use crate::pheromones::PheromoneSense;
+
+/// Everything the host tells a plugin about the surroundings of an ant in one tick
+#[repr(C)]
+pub struct AntContext<'a> {
+    /// Ants and food within vision range
+    pub vision: FfiSlice<'a, Vision>,
+    pub pheromones: PheromoneSense,
+    /// The ant's own memory block, see [`AntMemory`]
+    pub memory: AntMemory,
+    /// Generator for this ant and tick, derived from the round seed
+    pub rng: SimRng,
+    /// Ticks since the round started
+    pub tick: u64,
+    /// Result of the food action of the previous tick, `FoodAction::None` if there was none
+    pub food_outcome: FoodOutcome,
+    /// The nest of the ant called its ants home in this tick
+    pub recalled: bool,
+    /// Signal a nest of the colony broadcast in this tick, 0 if there was none
+    pub signal: u32,
+}
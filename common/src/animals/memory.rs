@@ -0,0 +1,55 @@
+/// View of the memory block the host keeps for every ant.
+///
+/// The size is declared by the plugin through `ant_memory_size`, the block is zeroed when the
+/// ant is spawned and freed when the ant dies. It is aligned to 8 bytes.
+#[repr(C)]
+pub struct AntMemory {
+    pub(crate) data: *mut u8,
+    pub(crate) len: usize,
+}
+
+impl AntMemory {
+    pub const ALIGN: usize = 8;
+
+    /// # Safety
+    /// `data` has to point to `len` writable bytes aligned to [`AntMemory::ALIGN`],
+    /// which stay valid and unaliased for as long as the view is used.
+    pub unsafe fn from_raw(data: *mut u8, len: usize) -> AntMemory {
+        AntMemory { data, len }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        if self.len == 0 {
+            return &[];
+        }
+        unsafe { std::slice::from_raw_parts(self.data, self.len) }
+    }
+
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+        if self.len == 0 {
+            return &mut [];
+        }
+        unsafe { std::slice::from_raw_parts_mut(self.data, self.len) }
+    }
+
+    /// Interprets the block as a `T`. Returns `None` if `T` does not fit into the block
+    /// or needs a larger alignment.
+    ///
+    /// # Safety
+    /// All bytes zero has to be a valid `T`, as that is what a new ant starts with.
+    /// `T` should be `#[repr(C)]` and must not contain pointers or references.
+    pub unsafe fn get_mut<T: Copy>(&mut self) -> Option<&mut T> {
+        if std::mem::size_of::<T>() > self.len || std::mem::align_of::<T>() > Self::ALIGN {
+            return None;
+        }
+        Some(&mut *(self.data as *mut T))
+    }
+}
@@ -1 +1,3 @@
 pub mod ant;
+pub mod context;
+pub mod memory;
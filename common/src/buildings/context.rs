@@ -0,0 +1,21 @@
+use crate::helper::SimRng;
+
+/// Everything the host tells a plugin about the colony of a nest in one tick
+#[repr(C)]
+pub struct NestContext {
+    /// Generator for this nest and tick, derived from the round seed
+    pub rng: SimRng,
+    /// Ticks since the round started
+    pub tick: u64,
+    /// Ants of the colony alive at the end of the previous tick
+    pub living_ants: u32,
+    /// Food delivered to all nests of the colony in the last [`NestContext::RECENT_TICKS`] ticks,
+    /// not counting the current one
+    pub recent_food: u32,
+    /// Ants of the colony that died since the round started
+    pub ants_lost: u32,
+}
+
+impl NestContext {
+    pub const RECENT_TICKS: usize = 100;
+}
@@ -1,20 +1,97 @@
+pub mod context;
+
+use crate::animals::ant::Caste;
+use crate::helper::config::NestConfig;
 use crate::helper::Vector2D;
+use serde::{Deserialize, Serialize};
 
 #[repr(C)]
+#[derive(Clone, Copy, Serialize, Deserialize)]
 pub enum NestAction {
     Nothing,
-    SpawnAnts(usize),
+    /// Spawns that many ants of the caste, as long as the nest can pay for them
+    SpawnAnts(usize, Caste),
+    /// Founds a satellite nest of the colony at the position and hands it that much energy
+    FoundNest(Vector2D, u32),
+    /// Moves up to that much energy into the granary
+    StoreFood(u32),
+    /// Takes up to that much energy out of the granary
+    TakeFood(u32),
+    /// Calls the ants of this nest home, they see it in their next update
+    RecallAnts,
+    /// Sends a signal to every ant of the colony, they see it in their next update
+    Broadcast(u32),
 }
 
+/// Why the last `SpawnAnts` of a nest spawned fewer ants than it asked for, or why its last
+/// `FoundNest` was refused
 #[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SpawnDenial {
+    /// Every ant was spawned, or the nest did not ask for any
+    None,
+    /// Paying for another ant would have taken the energy down to the reserve
+    NotEnoughEnergy,
+    /// The nest already has as many living ants as it may have
+    PopulationLimit,
+    /// The site of the new nest is out of range, outside the world or inside an obstacle
+    InvalidSite,
+}
+
+impl std::fmt::Display for SpawnDenial {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SpawnDenial::None => write!(f, "-"),
+            SpawnDenial::NotEnoughEnergy => write!(f, "Not enough energy"),
+            SpawnDenial::PopulationLimit => write!(f, "Population limit"),
+            SpawnDenial::InvalidSite => write!(f, "Invalid site"),
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Serialize, Deserialize)]
 pub struct Nest {
     pub id: usize,
+    /// Id of the first nest of the colony, satellite nests share it
+    pub colony: usize,
     pub pos: Vector2D,
     pub energy: u32,
     pub rounds_to_energy_loss: u32,
+    /// Outcome of the nest action of the previous tick
+    pub spawn_denied: SpawnDenial,
+    /// Energy put aside, it decays slower but cannot be spent before it is taken out again
+    pub granary: u32,
 }
 
 impl Nest {
+    pub fn new_at_pos(id: usize, pos: Vector2D, config: &NestConfig) -> Nest {
+        Nest {
+            id,
+            colony: id,
+            pos,
+            energy: config.start_energy,
+            rounds_to_energy_loss: config.energy_loss_rounds,
+            spawn_denied: SpawnDenial::None,
+            granary: 0,
+        }
+    }
+
+    /// A new nest of the colony of `parent`, starting with `energy`
+    pub fn new_satellite(
+        id: usize,
+        parent: &Nest,
+        pos: Vector2D,
+        energy: u32,
+        config: &NestConfig,
+    ) -> Nest {
+        Nest {
+            colony: parent.colony,
+            energy,
+            ..Nest::new_at_pos(id, pos, config)
+        }
+    }
+
     pub fn is_alive(&self) -> bool {
         self.energy > 0
     }
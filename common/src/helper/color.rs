@@ -1,4 +1,7 @@
-#[derive(Copy, Clone)]
+use serde::{Deserialize, Serialize};
+
+#[repr(C)]
+#[derive(Copy, Clone, Serialize, Deserialize)]
 pub struct Color([f32; 4]);
 impl Color {
     pub const fn new(r: f32, g: f32, b: f32, a: f32) -> Color {
@@ -30,3 +33,4 @@ pub const GREEN: Color = Color::new(0.0, 1.0, 0.0, 1.0);
 pub const BLUE: Color = Color::new(0.0, 0.0, 1.0, 1.0);
 pub const RED: Color = Color::new(1.0, 0.0, 0.0, 1.0);
 pub const BLACK: Color = Color::new(0.0, 0.0, 0.0, 1.0);
+pub const GRAY: Color = Color::new(0.4, 0.4, 0.4, 1.0);
@@ -1,4 +1,19 @@
-pub struct GeneralConfig {}
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::Vector2D;
+use crate::animals::ant::Caste;
+use crate::terrain::Obstacle;
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GeneralConfig {
+    /// Seed for all randomness of a round, world generation as well as the plugin generators
+    pub seed: u64,
+    /// Panics when the energy of the world changes by anything else than spawns and decay
+    pub check_energy: bool,
+}
 
 impl Default for GeneralConfig {
     fn default() -> Self {
@@ -8,15 +23,95 @@ impl Default for GeneralConfig {
 
 impl GeneralConfig {
     pub fn new() -> GeneralConfig {
-        GeneralConfig {}
+        GeneralConfig {
+            seed: 0,
+            check_energy: false,
+        }
+    }
+}
+
+/// Size, obstacles and fixed food sources of the ground, take effect with the next round
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorldConfig {
+    pub width: f32,
+    pub height: f32,
+    pub obstacles: Vec<Obstacle>,
+    pub food_sources: Vec<FoodSourceConfig>,
+}
+
+impl Default for WorldConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WorldConfig {
+    pub fn new() -> WorldConfig {
+        WorldConfig {
+            width: 1000.0,
+            height: 1000.0,
+            obstacles: Vec::new(),
+            food_sources: Vec::new(),
+        }
     }
+
+    pub fn size(&self) -> Vector2D {
+        Vector2D::new(self.width, self.height)
+    }
+}
+
+/// A place where food grows back after it was eaten, like a bush
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FoodSourceConfig {
+    pub position: Vector2D,
+    /// Most nutrition the source holds at once, it starts full
+    pub capacity: u32,
+    /// Nutrition growing back per tick
+    pub regrowth: u32,
 }
 
+/// How new nests or food pellets are spread over the ground
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum Placement {
+    /// Anywhere on the ground
+    Uniform,
+    /// Within `radius` around one of `patches` random points, chosen once per round
+    Clustered { patches: u32, radius: f32 },
+    /// At the given points in turn, starting over after the last one
+    Fixed { points: Vec<Vector2D> },
+    /// At `radius` around a random nest, nests themselves around the center of the ground
+    Ring { radius: f32 },
+    /// Copies rotated around the center of the ground, one for every colony, so no colony is
+    /// closer to anything than another
+    Mirrored,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct NestConfig {
+    /// Number of competing nests at the start of a round
+    pub colonies: u32,
     pub max_energy: u32,
     pub start_energy: u32,
     pub energy_loss_amount: u32,
     pub energy_loss_rounds: u32,
+    /// Energy a nest pays for every ant, plus the energy cost of its caste
+    pub spawn_cost: u32,
+    /// Spawning never takes the energy of a nest down to this or below
+    pub min_reserve: u32,
+    /// Most living ants a nest may have, 0 for no limit
+    pub max_population: u32,
+    /// Most energy the granary of a nest holds
+    pub granary_capacity: u32,
+    pub granary_loss_amount: u32,
+    pub granary_loss_rounds: u32,
+    /// Energy lost when founding a satellite nest, on top of what the new nest gets
+    pub found_cost: u32,
+    /// Farthest a satellite nest can be founded from its parent
+    pub found_range: f32,
+    pub placement: Placement,
 }
 
 impl Default for NestConfig {
@@ -28,23 +123,37 @@ impl Default for NestConfig {
 impl NestConfig {
     pub fn new() -> NestConfig {
         NestConfig {
+            colonies: 1,
             max_energy: 10000,
             start_energy: 4000,
             energy_loss_amount: 1,
             energy_loss_rounds: 5,
+            spawn_cost: 500,
+            min_reserve: 0,
+            max_population: 0,
+            granary_capacity: 20000,
+            granary_loss_amount: 1,
+            granary_loss_rounds: 25,
+            found_cost: 2000,
+            found_range: 300.0,
+            placement: Placement::Uniform,
         }
     }
 }
 
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AntConfig {
     pub max_energy: u32,
-    pub speed: f32,
     pub angular_speed: f32,
-    pub vision_range: f32,
-    pub energy_loss_amount: u32,
     pub energy_loss_rounds: u32,
     pub mouth_reach: f32,
-    pub carry_capacity: u32,
+    /// Ticks an ant lives at most
+    pub lifespan: u32,
+    pub worker: CasteConfig,
+    pub scout: CasteConfig,
+    pub soldier: CasteConfig,
+    pub carrier: CasteConfig,
 }
 
 impl Default for AntConfig {
@@ -57,22 +166,110 @@ impl AntConfig {
     pub fn new() -> AntConfig {
         AntConfig {
             max_energy: 1000,
-            speed: 1.0,
             angular_speed: 0.1,
-            vision_range: 100.0,
-            energy_loss_amount: 1,
             energy_loss_rounds: 5,
             mouth_reach: 7.0,
+            lifespan: 20000,
+            worker: CasteConfig::new(),
+            scout: CasteConfig {
+                speed: 1.5,
+                vision_range: 150.0,
+                carry_capacity: 1000,
+                energy_cost: 100,
+                energy_loss_amount: 2,
+            },
+            soldier: CasteConfig {
+                speed: 1.0,
+                vision_range: 80.0,
+                carry_capacity: 1000,
+                energy_cost: 300,
+                energy_loss_amount: 2,
+            },
+            carrier: CasteConfig {
+                speed: 0.7,
+                vision_range: 60.0,
+                carry_capacity: 10000,
+                energy_cost: 200,
+                energy_loss_amount: 1,
+            },
+        }
+    }
+
+    pub fn caste(&self, caste: Caste) -> &CasteConfig {
+        match caste {
+            Caste::Worker => &self.worker,
+            Caste::Scout => &self.scout,
+            Caste::Soldier => &self.soldier,
+            Caste::Carrier => &self.carrier,
+        }
+    }
+
+    pub fn caste_mut(&mut self, caste: Caste) -> &mut CasteConfig {
+        match caste {
+            Caste::Worker => &mut self.worker,
+            Caste::Scout => &mut self.scout,
+            Caste::Soldier => &mut self.soldier,
+            Caste::Carrier => &mut self.carrier,
+        }
+    }
+
+    /// Farthest any ant can see
+    pub fn max_vision_range(&self) -> f32 {
+        Caste::ALL
+            .iter()
+            .map(|&caste| self.caste(caste).vision_range)
+            .fold(0.0, f32::max)
+    }
+}
+
+/// Stats of the ants of one caste
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CasteConfig {
+    pub speed: f32,
+    pub vision_range: f32,
+    pub carry_capacity: u32,
+    /// Energy a nest pays for an ant of this caste on top of the spawn cost
+    pub energy_cost: u32,
+    /// Upkeep, lost every `ants.energy_loss_rounds` ticks
+    pub energy_loss_amount: u32,
+}
+
+impl Default for CasteConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CasteConfig {
+    pub fn new() -> CasteConfig {
+        CasteConfig {
+            speed: 1.0,
+            vision_range: 100.0,
             carry_capacity: 4000,
+            energy_cost: 0,
+            energy_loss_amount: 1,
         }
     }
 }
 
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct FoodConfig {
     pub nutrition: u32,
     pub eaten_value: u32,
+    /// Ticks between two random pellets, 0 turns them off
     pub spawn_time: i32,
     pub start_amount: i32,
+    /// Number of food sources spread over the ground at the start of a round, in addition to
+    /// the fixed ones of the world. Unlike pellets they grow back after they were eaten.
+    pub sources: u32,
+    /// Most nutrition one source holds at once
+    pub source_capacity: u32,
+    /// Nutrition growing back per tick and source
+    pub source_regrowth: u32,
+    pub placement: Placement,
+    pub source_placement: Placement,
 }
 impl Default for FoodConfig {
     fn default() -> Self {
@@ -87,14 +284,89 @@ impl FoodConfig {
             eaten_value: 1,
             spawn_time: 100,
             start_amount: 10,
+            sources: 0,
+            source_capacity: 5000,
+            source_regrowth: 2,
+            placement: Placement::Uniform,
+            source_placement: Placement::Uniform,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PheromoneConfig {
+    /// Edge length of one grid cell
+    pub cell_size: f32,
+    /// Fraction of pheromone lost per tick
+    pub evaporation: f32,
+    /// Fraction of pheromone spreading to the neighbour cells per tick
+    pub diffusion: f32,
+    /// Distance in front of the ant at which pheromones are sensed
+    pub sense_distance: f32,
+    /// Angle between the center and the left/right sample in radians
+    pub sense_angle: f32,
+    /// Maximum amount an ant can deposit in one tick
+    pub max_deposit: f32,
+}
+
+impl Default for PheromoneConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PheromoneConfig {
+    pub fn new() -> PheromoneConfig {
+        PheromoneConfig {
+            cell_size: 10.0,
+            evaporation: 0.002,
+            diffusion: 0.05,
+            sense_distance: 15.0,
+            sense_angle: 0.5,
+            max_deposit: 10.0,
+        }
+    }
+}
+
+/// Weights of the metrics that make up the fitness of a nest
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScoreConfig {
+    pub food_delivered: f64,
+    pub ants_spawned: f64,
+    pub ant_ticks: f64,
+    pub energy_integral: f64,
+    pub survival_ticks: f64,
+}
+
+impl Default for ScoreConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ScoreConfig {
+    pub fn new() -> ScoreConfig {
+        ScoreConfig {
+            food_delivered: 1.0,
+            ants_spawned: 100.0,
+            ant_ticks: 0.01,
+            energy_integral: 0.001,
+            survival_ticks: 1.0,
         }
     }
 }
 
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Config {
+    pub world: WorldConfig,
     pub ants: AntConfig,
     pub food: FoodConfig,
     pub nests: NestConfig,
+    pub pheromones: PheromoneConfig,
+    pub score: ScoreConfig,
     pub general: GeneralConfig,
 }
 
@@ -107,10 +379,217 @@ impl Default for Config {
 impl Config {
     pub fn new() -> Config {
         Config {
+            world: WorldConfig::new(),
             ants: AntConfig::new(),
             food: FoodConfig::new(),
             nests: NestConfig::new(),
+            pheromones: PheromoneConfig::new(),
+            score: ScoreConfig::new(),
             general: GeneralConfig::new(),
         }
     }
+
+    /// Checks for values the simulation cannot run with
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let positive = [
+            ("world.width", self.world.width),
+            ("world.height", self.world.height),
+            ("ants.max_energy", self.ants.max_energy as f32),
+            ("nests.max_energy", self.nests.max_energy as f32),
+            ("ants.worker.speed", self.ants.worker.speed),
+            ("ants.scout.speed", self.ants.scout.speed),
+            ("ants.soldier.speed", self.ants.soldier.speed),
+            ("ants.carrier.speed", self.ants.carrier.speed),
+            (
+                "ants.energy_loss_rounds",
+                self.ants.energy_loss_rounds as f32,
+            ),
+            ("ants.mouth_reach", self.ants.mouth_reach),
+            ("ants.lifespan", self.ants.lifespan as f32),
+            (
+                "nests.energy_loss_rounds",
+                self.nests.energy_loss_rounds as f32,
+            ),
+            (
+                "nests.granary_loss_rounds",
+                self.nests.granary_loss_rounds as f32,
+            ),
+            ("pheromones.cell_size", self.pheromones.cell_size),
+        ];
+        for (name, value) in positive {
+            if value.is_nan() || value <= 0.0 {
+                return Err(ConfigError::NotPositive(name));
+            }
+        }
+
+        let fractions = [
+            ("pheromones.evaporation", self.pheromones.evaporation),
+            ("pheromones.diffusion", self.pheromones.diffusion),
+        ];
+        for (name, value) in fractions {
+            if !(0.0..=1.0).contains(&value) {
+                return Err(ConfigError::NotAFraction(name, value));
+            }
+        }
+
+        // With how many things each placement starts a round
+        let placements = [
+            (
+                "food.placement",
+                &self.food.placement,
+                self.food.start_amount.max(0) as usize,
+            ),
+            (
+                "nests.placement",
+                &self.nests.placement,
+                self.nests.colonies as usize,
+            ),
+            (
+                "food.source_placement",
+                &self.food.source_placement,
+                self.food.sources as usize,
+            ),
+        ];
+        for (name, placement, needed) in placements {
+            let valid = match placement {
+                Placement::Uniform | Placement::Mirrored => true,
+                Placement::Clustered { patches, radius } => *patches > 0 && *radius >= 0.0,
+                Placement::Fixed { points } => !points.is_empty(),
+                Placement::Ring { radius } => *radius >= 0.0,
+            };
+            if !valid {
+                return Err(ConfigError::InvalidPlacement(name));
+            }
+            if let Placement::Fixed { points } = placement {
+                if points.len() < needed {
+                    return Err(ConfigError::TooFewPoints {
+                        name,
+                        points: points.len(),
+                        needed,
+                    });
+                }
+            }
+        }
+
+        if let Some(idx) = self.world.obstacles.iter().position(|o| !o.is_valid()) {
+            return Err(ConfigError::InvalidObstacle(idx));
+        }
+
+        if self.food.sources > 0 && self.food.source_capacity == 0 {
+            return Err(ConfigError::NotPositive("food.source_capacity"));
+        }
+
+        if let Some(idx) = self
+            .world
+            .food_sources
+            .iter()
+            .position(|source| source.capacity == 0)
+        {
+            return Err(ConfigError::EmptyFoodSource(idx));
+        }
+
+        if self.nests.start_energy > self.nests.max_energy {
+            return Err(ConfigError::StartAboveMax {
+                start_energy: self.nests.start_energy,
+                max_energy: self.nests.max_energy,
+            });
+        }
+
+        for caste in Caste::ALL {
+            let vision_range = self.ants.caste(caste).vision_range;
+            if vision_range < self.ants.mouth_reach {
+                return Err(ConfigError::VisionBelowReach {
+                    caste,
+                    vision_range,
+                    mouth_reach: self.ants.mouth_reach,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A config value the simulation cannot run with
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigError {
+    NotPositive(&'static str),
+    NotAFraction(&'static str, f32),
+    /// No patches, no fixed points or a negative radius
+    InvalidPlacement(&'static str),
+    /// A fixed placement has fewer points than things to place at the start of a round
+    TooFewPoints {
+        name: &'static str,
+        points: usize,
+        needed: usize,
+    },
+    /// Index of an obstacle without an inside
+    InvalidObstacle(usize),
+    /// Index of a food source with a capacity of 0
+    EmptyFoodSource(usize),
+    /// Nests would start with more energy than they can hold
+    StartAboveMax {
+        start_energy: u32,
+        max_energy: u32,
+    },
+    /// Ants could reach food they cannot see
+    VisionBelowReach {
+        caste: Caste,
+        vision_range: f32,
+        mouth_reach: f32,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            ConfigError::NotPositive(name) => write!(f, "{} has to be greater than 0", name),
+            ConfigError::NotAFraction(name, value) => {
+                write!(f, "{} has to be between 0 and 1, not {}", name, value)
+            }
+            ConfigError::InvalidPlacement(name) => {
+                write!(
+                    f,
+                    "{} needs at least one patch or point and a radius of at least 0",
+                    name
+                )
+            }
+            ConfigError::TooFewPoints {
+                name,
+                points,
+                needed,
+            } => write!(
+                f,
+                "{} has {} points but needs at least {}",
+                name, points, needed
+            ),
+            ConfigError::InvalidObstacle(idx) => {
+                write!(f, "world.obstacles[{}] has no inside", idx)
+            }
+            ConfigError::EmptyFoodSource(idx) => {
+                write!(f, "world.food_sources[{}] has no capacity", idx)
+            }
+            ConfigError::StartAboveMax {
+                start_energy,
+                max_energy,
+            } => write!(
+                f,
+                "nests.start_energy ({}) has to be at most nests.max_energy ({})",
+                start_energy, max_energy
+            ),
+            ConfigError::VisionBelowReach {
+                caste,
+                vision_range,
+                mouth_reach,
+            } => write!(
+                f,
+                "the vision range of the {} caste ({}) has to be at least ants.mouth_reach ({})",
+                caste.to_string().to_lowercase(),
+                vision_range,
+                mouth_reach
+            ),
+        }
+    }
 }
+
+impl std::error::Error for ConfigError {}
@@ -4,12 +4,16 @@ use crate::items::food::FoodPellet;
 pub mod config;
 
 mod color;
+mod random;
 mod rotation;
+mod slice;
 
 mod vector2d;
 
 pub use color::*;
+pub use random::*;
 pub use rotation::*;
+pub use slice::*;
 pub use vector2d::*;
 
 #[repr(C)]
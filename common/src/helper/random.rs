@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+
+/// Small deterministic random number generator (SplitMix64).
+///
+/// The host seeds all of its randomness from `GeneralConfig::seed` and hands
+/// a fresh generator to the plugins each tick, so a round can be replayed
+/// exactly by running it again with the same seed.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+pub struct SimRng {
+    state: u64,
+}
+
+const GOLDEN_GAMMA: u64 = 0x9E37_79B9_7F4A_7C15;
+
+const fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+impl SimRng {
+    pub const fn new(seed: u64) -> SimRng {
+        SimRng { state: seed }
+    }
+
+    /// Creates an independent generator for the given stream, e.g. a tick or an entity id.
+    /// Deriving does not advance `self`.
+    pub const fn derive(&self, stream: u64) -> SimRng {
+        SimRng::new(mix(self.state ^ mix(stream.wrapping_add(GOLDEN_GAMMA))))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(GOLDEN_GAMMA);
+        mix(self.state)
+    }
+
+    /// Uniform float in `[0, 1)`
+    pub fn gen_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Uniform integer in `[low, high)`. Panics if the range is empty.
+    pub fn gen_range(&mut self, low: u32, high: u32) -> u32 {
+        assert!(low < high, "gen_range called with empty range");
+        low + (self.next_u64() % u64::from(high - low)) as u32
+    }
+}
@@ -1,6 +1,8 @@
+use serde::{Deserialize, Serialize};
 use std::ops::{Add, AddAssign, SubAssign};
 
-#[derive(Copy, Clone)]
+#[repr(C)]
+#[derive(Copy, Clone, Serialize, Deserialize)]
 pub struct Rotation(f32);
 impl Rotation {
     pub fn new_rad(rad: f32) -> Rotation {
@@ -0,0 +1,53 @@
+use std::marker::PhantomData;
+
+/// Read only view of a slice with a stable layout, so it can cross the plugin boundary
+#[repr(C)]
+pub struct FfiSlice<'a, T> {
+    pub(crate) ptr: *const T,
+    pub(crate) len: usize,
+    _marker: PhantomData<&'a T>,
+}
+
+impl<'a, T> FfiSlice<'a, T> {
+    pub fn new(slice: &'a [T]) -> FfiSlice<'a, T> {
+        FfiSlice {
+            ptr: slice.as_ptr(),
+            len: slice.len(),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn as_slice(&self) -> &'a [T] {
+        if self.len == 0 {
+            return &[];
+        }
+        unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'a, T> {
+        self.as_slice().iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<'a, T> From<&'a [T]> for FfiSlice<'a, T> {
+    fn from(slice: &'a [T]) -> Self {
+        FfiSlice::new(slice)
+    }
+}
+
+impl<'a, T> IntoIterator for &FfiSlice<'a, T> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
@@ -1,8 +1,10 @@
 use super::Rotation;
+use serde::{Deserialize, Serialize};
 use std::ops::{Add, AddAssign, Mul, Sub};
 
 #[repr(C)]
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct Vector2D([f32; 2]);
 impl Vector2D {
     pub fn new(x: f32, y: f32) -> Vector2D {
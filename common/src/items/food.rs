@@ -1,7 +1,8 @@
 use crate::helper::*;
+use serde::{Deserialize, Serialize};
 
 #[repr(C)]
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Serialize, Deserialize)]
 pub struct FoodPellet {
     pub position: Vector2D,
     pub nutrition: u32,
@@ -16,20 +17,55 @@ impl PartialEq<FoodPellet> for FoodPellet {
 }
 
 impl FoodPellet {
+    pub fn new_at_pos(id: usize, position: Vector2D, nutrition: u32, bite_size: u32) -> FoodPellet {
+        FoodPellet {
+            position,
+            nutrition,
+            bite_size,
+            id,
+        }
+    }
+
     pub fn get_position(&self) -> Vector2D {
         self.position
     }
 
     pub fn get_eaten(&mut self) -> u32 {
-        if self.nutrition >= self.bite_size {
-            self.nutrition -= self.bite_size;
-            self.bite_size
-        } else {
-            self.nutrition
-        }
+        self.take(self.bite_size)
+    }
+
+    /// Removes up to `amount` nutrition from the pellet, returns how much was removed
+    pub fn take(&mut self, amount: u32) -> u32 {
+        let taken = amount.min(self.nutrition);
+        self.nutrition -= taken;
+        taken
     }
 
     pub fn is_some_left(&self) -> bool {
         self.nutrition > 0
     }
 }
+
+/// Which food action an ant took
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum FoodAction {
+    #[default]
+    None,
+    Eat,
+    Carry,
+}
+
+/// What an ant got out of its `EatFood` or `CarryFood` action.
+///
+/// If several ants want the same pellet in one tick, the ant that was spawned first is served first,
+/// the others share what is left in the same order.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct FoodOutcome {
+    pub action: FoodAction,
+    pub food_id: usize,
+    /// What the ant would have got if it had been alone at the pellet
+    pub requested: u32,
+    pub received: u32,
+}
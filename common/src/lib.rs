@@ -1,4 +1,7 @@
+pub mod abi;
 pub mod animals;
 pub mod buildings;
 pub mod helper;
 pub mod items;
+pub mod pheromones;
+pub mod terrain;
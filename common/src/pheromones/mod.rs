@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PheromoneKind {
+    Home,
+    Food,
+    Alarm,
+}
+
+impl PheromoneKind {
+    pub const COUNT: usize = 3;
+    pub const ALL: [PheromoneKind; PheromoneKind::COUNT] =
+        [PheromoneKind::Home, PheromoneKind::Food, PheromoneKind::Alarm];
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Pheromone strength of one kind at three points in front of the ant
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PheromoneSample {
+    pub left: f32,
+    pub center: f32,
+    pub right: f32,
+}
+
+/// Everything an ant smells in one tick, one sample per pheromone kind
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PheromoneSense {
+    pub samples: [PheromoneSample; PheromoneKind::COUNT],
+}
+
+impl PheromoneSense {
+    pub fn get(&self, kind: PheromoneKind) -> PheromoneSample {
+        self.samples[kind.index()]
+    }
+}
@@ -0,0 +1,289 @@
+use crate::helper::Vector2D;
+use serde::{Deserialize, Serialize};
+
+/// Static shape on the ground that ants can neither walk nor see through
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "shape")]
+pub enum Obstacle {
+    /// Axis aligned rectangle between the corners `min` and `max`
+    Rect {
+        min: Vector2D,
+        max: Vector2D,
+    },
+    Circle {
+        center: Vector2D,
+        radius: f32,
+    },
+    /// Outline of a polygon that does not cross itself, the last point connects back to the first
+    Polygon {
+        points: Vec<Vector2D>,
+    },
+}
+
+impl Obstacle {
+    pub fn contains(&self, pos: Vector2D) -> bool {
+        match self {
+            Obstacle::Rect { min, max } => {
+                pos.x() >= min.x() && pos.x() <= max.x() && pos.y() >= min.y() && pos.y() <= max.y()
+            }
+            Obstacle::Circle { center, radius } => pos.distance(*center) <= *radius,
+            Obstacle::Polygon { points } => polygon_contains(points, pos),
+        }
+    }
+
+    /// True if the straight line from `from` to `to` touches the obstacle
+    pub fn blocks(&self, from: Vector2D, to: Vector2D) -> bool {
+        match self {
+            Obstacle::Circle { center, radius } => {
+                distance_to_segment(*center, from, to) <= *radius
+            }
+            Obstacle::Rect { .. } | Obstacle::Polygon { .. } => {
+                let outline = self.outline(0);
+                self.contains(from)
+                    || self.contains(to)
+                    || edges(&outline).any(|(a, b)| segments_intersect(from, to, a, b))
+            }
+        }
+    }
+
+    /// Corners of the obstacle, circles are approximated by `circle_segments` corners
+    pub fn outline(&self, circle_segments: usize) -> Vec<Vector2D> {
+        match self {
+            Obstacle::Rect { min, max } => vec![
+                *min,
+                Vector2D::new(max.x(), min.y()),
+                *max,
+                Vector2D::new(min.x(), max.y()),
+            ],
+            Obstacle::Circle { center, radius } => (0..circle_segments)
+                .map(|i| {
+                    let angle = std::f32::consts::TAU * i as f32 / circle_segments as f32;
+                    *center + Vector2D::new(angle.cos(), angle.sin()) * *radius
+                })
+                .collect(),
+            Obstacle::Polygon { points } => points.clone(),
+        }
+    }
+
+    /// False for shapes without an inside, like rectangles with `min` above `max`
+    pub fn is_valid(&self) -> bool {
+        match self {
+            Obstacle::Rect { min, max } => min.x() < max.x() && min.y() < max.y(),
+            Obstacle::Circle { radius, .. } => *radius > 0.0,
+            Obstacle::Polygon { points } => points.len() >= 3,
+        }
+    }
+}
+
+fn cross(a: Vector2D, b: Vector2D) -> f32 {
+    a.x() * b.y() - a.y() * b.x()
+}
+
+fn dot(a: Vector2D, b: Vector2D) -> f32 {
+    a.x() * b.x() + a.y() * b.y()
+}
+
+/// All sides of a closed outline
+fn edges(points: &[Vector2D]) -> impl Iterator<Item = (Vector2D, Vector2D)> + '_ {
+    points
+        .iter()
+        .zip(points.iter().cycle().skip(1))
+        .map(|(&a, &b)| (a, b))
+}
+
+/// Even-odd rule, points on the outline are inside like for rectangles and circles
+fn polygon_contains(points: &[Vector2D], pos: Vector2D) -> bool {
+    if edges(points).any(|(a, b)| cross(b - a, pos - a) == 0.0 && within_bounds(a, b, pos)) {
+        return true;
+    }
+
+    let mut inside = false;
+    for (a, b) in edges(points) {
+        if (a.y() > pos.y()) != (b.y() > pos.y()) {
+            let x = a.x() + (pos.y() - a.y()) / (b.y() - a.y()) * (b.x() - a.x());
+            if pos.x() < x {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
+
+fn distance_to_segment(pos: Vector2D, a: Vector2D, b: Vector2D) -> f32 {
+    let ab = b - a;
+    let length_squared = dot(ab, ab);
+    if length_squared == 0.0 {
+        return pos.distance(a);
+    }
+    let t = (dot(pos - a, ab) / length_squared).clamp(0.0, 1.0);
+    pos.distance(a + ab * t)
+}
+
+/// `pos` is known to be on the line through `a` and `b`
+fn within_bounds(a: Vector2D, b: Vector2D, pos: Vector2D) -> bool {
+    pos.x() >= a.x().min(b.x())
+        && pos.x() <= a.x().max(b.x())
+        && pos.y() >= a.y().min(b.y())
+        && pos.y() <= a.y().max(b.y())
+}
+
+fn segments_intersect(p1: Vector2D, p2: Vector2D, q1: Vector2D, q2: Vector2D) -> bool {
+    let d1 = cross(q2 - q1, p1 - q1);
+    let d2 = cross(q2 - q1, p2 - q1);
+    let d3 = cross(p2 - p1, q1 - p1);
+    let d4 = cross(p2 - p1, q2 - p1);
+
+    if d1 * d2 < 0.0 && d3 * d4 < 0.0 {
+        return true;
+    }
+
+    (d1 == 0.0 && within_bounds(q1, q2, p1))
+        || (d2 == 0.0 && within_bounds(q1, q2, p2))
+        || (d3 == 0.0 && within_bounds(p1, p2, q1))
+        || (d4 == 0.0 && within_bounds(p1, p2, q2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v(x: f32, y: f32) -> Vector2D {
+        Vector2D::new(x, y)
+    }
+
+    /// A U open to the top, the notch between the arms is outside
+    fn u_shape() -> Obstacle {
+        Obstacle::Polygon {
+            points: vec![
+                v(0.0, 0.0),
+                v(30.0, 0.0),
+                v(30.0, 30.0),
+                v(20.0, 30.0),
+                v(20.0, 10.0),
+                v(10.0, 10.0),
+                v(10.0, 30.0),
+                v(0.0, 30.0),
+            ],
+        }
+    }
+
+    #[test]
+    fn crossing_segments_intersect() {
+        assert!(segments_intersect(
+            v(0.0, 0.0),
+            v(10.0, 10.0),
+            v(0.0, 10.0),
+            v(10.0, 0.0)
+        ));
+        assert!(!segments_intersect(
+            v(0.0, 0.0),
+            v(10.0, 0.0),
+            v(0.0, 1.0),
+            v(10.0, 1.0)
+        ));
+        assert!(!segments_intersect(
+            v(0.0, 0.0),
+            v(4.0, 4.0),
+            v(0.0, 10.0),
+            v(10.0, 0.0)
+        ));
+    }
+
+    #[test]
+    fn collinear_segments_intersect_only_if_they_overlap() {
+        assert!(segments_intersect(
+            v(0.0, 0.0),
+            v(10.0, 0.0),
+            v(5.0, 0.0),
+            v(15.0, 0.0)
+        ));
+        assert!(segments_intersect(
+            v(0.0, 0.0),
+            v(10.0, 0.0),
+            v(2.0, 0.0),
+            v(8.0, 0.0)
+        ));
+        assert!(!segments_intersect(
+            v(0.0, 0.0),
+            v(4.0, 0.0),
+            v(6.0, 0.0),
+            v(10.0, 0.0)
+        ));
+        assert!(!segments_intersect(
+            v(0.0, 0.0),
+            v(4.0, 4.0),
+            v(6.0, 6.0),
+            v(10.0, 10.0)
+        ));
+    }
+
+    #[test]
+    fn touching_segments_intersect() {
+        // End to end
+        assert!(segments_intersect(
+            v(0.0, 0.0),
+            v(5.0, 0.0),
+            v(5.0, 0.0),
+            v(5.0, 5.0)
+        ));
+        // End on the middle of the other one
+        assert!(segments_intersect(
+            v(0.0, 5.0),
+            v(5.0, 5.0),
+            v(5.0, 0.0),
+            v(5.0, 10.0)
+        ));
+        assert!(segments_intersect(
+            v(5.0, 0.0),
+            v(5.0, 10.0),
+            v(0.0, 5.0),
+            v(5.0, 5.0)
+        ));
+    }
+
+    #[test]
+    fn points_on_the_outline_of_a_polygon_are_inside() {
+        let triangle = Obstacle::Polygon {
+            points: vec![v(0.0, 0.0), v(10.0, 0.0), v(0.0, 10.0)],
+        };
+        for pos in [
+            v(5.0, 0.0),
+            v(0.0, 5.0),
+            v(5.0, 5.0),
+            v(10.0, 0.0),
+            v(0.0, 0.0),
+        ] {
+            assert!(triangle.contains(pos), "{:?}", pos);
+        }
+        assert!(triangle.contains(v(2.0, 2.0)));
+        assert!(!triangle.contains(v(5.1, 5.1)));
+        assert!(!triangle.contains(v(-0.1, 5.0)));
+    }
+
+    #[test]
+    fn the_notch_of_a_concave_polygon_is_outside() {
+        let u = u_shape();
+        assert!(u.contains(v(5.0, 20.0)));
+        assert!(u.contains(v(25.0, 20.0)));
+        assert!(u.contains(v(15.0, 5.0)));
+        assert!(!u.contains(v(15.0, 20.0)));
+        assert!(!u.contains(v(15.0, 35.0)));
+
+        // Down into the notch is free, sideways out of it goes through an arm
+        assert!(!u.blocks(v(15.0, 40.0), v(15.0, 15.0)));
+        assert!(u.blocks(v(15.0, 20.0), v(35.0, 20.0)));
+        // Touching the bottom of the notch is blocked
+        assert!(u.blocks(v(15.0, 20.0), v(15.0, 10.0)));
+    }
+
+    #[test]
+    fn lines_touching_a_circle_are_blocked() {
+        let circle = Obstacle::Circle {
+            center: v(0.0, 0.0),
+            radius: 5.0,
+        };
+        assert!(circle.blocks(v(-10.0, 5.0), v(10.0, 5.0)));
+        assert!(!circle.blocks(v(-10.0, 5.1), v(10.0, 5.1)));
+        assert!(circle.blocks(v(-10.0, 0.0), v(-4.0, 0.0)));
+    }
+}